@@ -1,35 +1,39 @@
 use crate::check_handler::{Location, LocationType, TX_LOCATION, take_away_received_item};
-use crate::constants::{MISSION_ITEM_MAP, REMOTE_ID};
+use crate::constants::{Character, Difficulty, MISSION_ITEM_MAP, REMOTE_ID};
 use crate::game_manager::{ARCHIPELAGO_DATA, ArchipelagoData, Style, get_mission};
 use crate::mapping::{
-    AutoHint, DeathlinkSetting, Goal, MAPPING, ModMode, ModModeData, OVERLAY_INFO, OverlayInfo,
-    get_adjudicators, get_secret_missions,
+    DeathlinkSetting, MAPPING, ModMode, ModModeData, OVERLAY_INFO, OverlayInfo, get_adjudicators,
+    get_secret_missions,
 };
+use crate::ui::chat::TX_CHAT;
 use crate::ui::overlay::{MessageSegment, MessageType, OverlayMessage};
-use crate::ui::{overlay, text_handler};
+use crate::ui::{chat, checklist, overlay, text_handler, text_markup};
 use crate::{
-    check_handler, constants, game_manager, hint_game, hook, location_handler, skill_manager,
-    utilities,
+    auto_hint, check_handler, constants, door_randomizer, enemy_randomizer, game_manager, goal,
+    hint_game, hook, location_handler, skill_manager, utilities,
 };
-use randomizer_utilities::ui::font_handler::{WHITE, YELLOW};
+use randomizer_utilities::ui::font_handler::{RED, WHITE, YELLOW};
+use std::collections::HashSet;
 use std::env;
 
 use crate::data::generated_locations;
 use crate::hint_game::TX_HINT;
 use archipelago_rs::{
-    AsItemId, Client, ClientStatus, Connection, ConnectionOptions, ConnectionState, CreateAsHint,
+    AsItemId, Client, Connection, ConnectionOptions, ConnectionState, CreateAsHint,
     DeathLinkOptions, Event, ItemHandling,
 };
 use randomizer_utilities::archipelago_utilities::{DeathLinkData, handle_print};
 use randomizer_utilities::item_sync::CURRENT_INDEX;
 use randomizer_utilities::{archipelago_utilities, item_sync, setup_channel_pair};
 use std::error::Error;
-use std::sync::OnceLock;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::mpsc::{Receiver, Sender, TryRecvError};
-use std::time::Duration;
+use std::sync::{OnceLock, RwLock};
+use std::time::{Duration, Instant};
 
 pub(crate) static CONNECTED: AtomicBool = AtomicBool::new(false);
+/// Last time the connection was confirmed to be up, None if we've never connected
+pub(crate) static LAST_SYNC: RwLock<Option<Instant>> = RwLock::new(None);
 pub static TX_DEATHLINK: OnceLock<Sender<DeathLinkData>> = OnceLock::new();
 
 pub struct ArchipelagoCore {
@@ -43,6 +47,11 @@ pub struct ArchipelagoCore {
     location_receiver: Receiver<Location>,
     deathlink_receiver: Receiver<DeathLinkData>,
     hint_receiver: Receiver<Vec<i64>>,
+    chat_receiver: Receiver<String>,
+    room_visit_receiver: Receiver<i32>,
+
+    /// Checked locations we already know about, used to spot locations checked by someone else
+    known_checked_locations: HashSet<i64>,
 }
 
 impl ArchipelagoCore {
@@ -64,6 +73,9 @@ impl ArchipelagoCore {
             location_receiver: setup_channel_pair(&TX_LOCATION),
             deathlink_receiver: setup_channel_pair(&TX_DEATHLINK),
             hint_receiver: setup_channel_pair(&TX_HINT),
+            chat_receiver: setup_channel_pair(&TX_CHAT),
+            room_visit_receiver: setup_channel_pair(&door_randomizer::TX_ROOM_VISIT),
+            known_checked_locations: HashSet::new(),
         })
     }
 
@@ -80,25 +92,10 @@ impl ArchipelagoCore {
                             overlay_info.client_version = mapping.client_version;
                             overlay_info.generated_version = None;
                             overlay_info.mode = ModMode::HintGame;
-                            if !self.hint_hooks_installed {
-                                unsafe {
-                                    match hint_game::create_hint_hooks() {
-                                        Ok(_) => {
-                                            log::debug!("Created DMC3 Hint Hooks");
-                                            self.hint_hooks_installed = true;
-                                        }
-                                        Err(err) => {
-                                            log::error!("Failed to create hint hooks: {:?}", err);
-                                        }
-                                    }
-                                }
-                            }
-                            if self.hint_hooks_installed && !self.hint_hooks_enabled {
-                                unsafe {
-                                    hint_game::enable_hint_hooks();
-                                    self.hint_hooks_enabled = true;
-                                }
-                            }
+                            install_hint_hooks(
+                                &mut self.hint_hooks_installed,
+                                &mut self.hint_hooks_enabled,
+                            );
                             hint_game::FLOORS_PER_HINT
                                 .store(mapping.floors_per_hint, Ordering::Relaxed);
                         }
@@ -108,6 +105,9 @@ impl ArchipelagoCore {
                             overlay_info.client_version = mapping.client_version;
                             overlay_info.mode = ModMode::Normal;
                             MAPPING.write()?.replace(mapping.clone());
+                            // Offline checks come back as checked by the server, they weren't collected
+                            self.known_checked_locations =
+                                get_known_checked_locations(self.connection.client().unwrap());
                             item_sync::send_offline_checks(self.connection.client_mut().unwrap())?;
                             if !self.hooks_installed {
                                 // Hooks needed to modify the game
@@ -127,7 +127,15 @@ impl ArchipelagoCore {
                                 hook::enable_hooks();
                                 self.hooks_enabled = true;
                             }
+                            // Bloody Palace floor monitoring is shared with the hint game
+                            install_hint_hooks(
+                                &mut self.hint_hooks_installed,
+                                &mut self.hint_hooks_enabled,
+                            );
                             run_setup(self.connection.client_mut().unwrap())?;
+                            goal::load_progress(self.connection.client().unwrap());
+                            door_randomizer::load_progress(self.connection.client().unwrap());
+                            goal::check_goal(self.connection.client_mut().unwrap())?;
                         }
                     }
                     // Print out version info
@@ -150,15 +158,20 @@ impl ArchipelagoCore {
                         );
                     }
                 }
-                Event::Updated(_) => {}
+                Event::Updated(_) => self.handle_remote_checks(),
                 Event::Print(print) => {
+                    let segments = chat::get_print_segments(&print);
                     let str = handle_print(print);
                     log::info!("Print from server: {}", str);
+                    chat::add_chat_message(segments);
                 }
                 Event::ReceivedItems(idx) => {
                     handle_received_items_packet(idx, self.connection.client_mut().unwrap())?;
                 }
-                Event::Error(err) => log::error!("{}", err),
+                Event::Error(err) => {
+                    log::error!("{}", err);
+                    chat::add_chat_message(vec![MessageSegment::new(err.to_string(), RED)]);
+                }
                 Event::Bounce {
                     games: _,
                     slots: _,
@@ -193,7 +206,7 @@ impl ArchipelagoCore {
                         // TODO May want to adjust position, currently added to the 'notification list' so it's in the upper right queue
                         0.0,
                         0.0,
-                        MessageType::Notification,
+                        MessageType::DeathLink,
                     ));
                     if let ModModeData::Normal(data) = self.connection.client().unwrap().slot_data()
                     {
@@ -220,22 +233,29 @@ impl ArchipelagoCore {
             ConnectionState::Connecting(_) => {}
             ConnectionState::Connected(_) => {
                 CONNECTED.store(true, Ordering::SeqCst);
+                *LAST_SYNC.write()? = Some(Instant::now());
             }
             ConnectionState::Disconnected(state) => {
-                CONNECTED.store(false, Ordering::SeqCst);
-                *OVERLAY_INFO.write()? = OverlayInfo::default();
-                disconnect(&mut self.hooks_enabled, &mut self.hint_hooks_enabled);
                 return Err(format!("Disconnected from server: {:?}", state).into());
             }
         }
         self.handle_channels()?;
+        if let Some(client) = self.connection.client_mut()
+            && let Err(err) = goal::update_progress(client)
+        {
+            log::error!("Failed to update goal progress: {}", err);
+        }
         Ok(())
     }
 
     pub fn handle_channels(&mut self) -> Result<(), Box<dyn Error>> {
         match self.location_receiver.try_recv() {
             Ok(location) => {
-                handle_item_receive(self.connection.client_mut().unwrap(), location)?;
+                handle_item_receive(
+                    self.connection.client_mut().unwrap(),
+                    location,
+                    &mut self.known_checked_locations,
+                )?;
             }
             Err(err) => {
                 if err == TryRecvError::Disconnected {
@@ -268,14 +288,125 @@ impl ArchipelagoCore {
                 }
             }
         }
+
+        match self.chat_receiver.try_recv() {
+            Ok(message) => {
+                // A failed send isn't worth dropping the connection over
+                if let Some(client) = self.connection.client_mut()
+                    && let Err(err) = client.say(message)
+                {
+                    log::error!("Failed to send chat message: {}", err);
+                    chat::add_chat_message(vec![MessageSegment::new(
+                        format!("Failed to send message: {}", err),
+                        RED,
+                    )]);
+                }
+            }
+            Err(err) => {
+                if err == TryRecvError::Disconnected {
+                    return Err("Disconnected from chat receiver".into());
+                }
+            }
+        }
+
+        match self.room_visit_receiver.try_recv() {
+            Ok(room) => {
+                if let Some(client) = self.connection.client_mut()
+                    && let Err(err) = door_randomizer::record_visit(client, room)
+                {
+                    log::error!("Failed to record room visit: {}", err);
+                }
+            }
+            Err(err) => {
+                if err == TryRecvError::Disconnected {
+                    return Err("Disconnected from room visit receiver".into());
+                }
+            }
+        }
         Ok(())
     }
+
+    /// Update the game for locations in our slot that were checked by someone else (!collect or a release)
+    fn handle_remote_checks(&mut self) {
+        let Some(client) = self.connection.client() else {
+            return;
+        };
+        if !matches!(client.slot_data(), ModModeData::Normal(_)) {
+            return;
+        }
+        let newly_checked: Vec<i64> = client
+            .checked_locations()
+            .map(|loc| loc.id())
+            .filter(|id| !self.known_checked_locations.contains(id))
+            .collect();
+        if newly_checked.is_empty() {
+            return;
+        }
+        self.known_checked_locations.extend(&newly_checked);
+        log::info!("{} locations were checked remotely", newly_checked.len());
+        if game_manager::session_is_valid() && !utilities::is_on_main_menu() {
+            // Items and events in the current room need to reflect the new state
+            hook::apply_relevant_key_items(client);
+            hook::patch_event_tables(client);
+            hook::refresh_item_spawns(client);
+            checklist::refresh(client, get_mission());
+        }
+        overlay::add_message(OverlayMessage::new(
+            vec![
+                MessageSegment::new("Collected ".to_string(), WHITE),
+                MessageSegment::new(newly_checked.len().to_string(), YELLOW),
+                MessageSegment::new(
+                    if newly_checked.len() == 1 {
+                        " location".to_string()
+                    } else {
+                        " locations".to_string()
+                    },
+                    WHITE,
+                ),
+            ],
+            Duration::from_secs(3),
+            0.0,
+            0.0,
+            MessageType::Notification,
+        ));
+        // A release/collect can also finish off the goal
+        if let Some(client) = self.connection.client_mut()
+            && let Err(err) = goal::check_goal(client)
+        {
+            log::error!("Failed to check goal: {}", err);
+        }
+    }
+
+    /// Undo everything the connection changed in the game, runs on every path that drops the connection
+    pub fn disconnect(&mut self) {
+        CONNECTED.store(false, Ordering::SeqCst);
+        if let Ok(mut overlay_info) = OVERLAY_INFO.write() {
+            *overlay_info = OverlayInfo::default();
+        }
+        disconnect(&mut self.hooks_enabled, &mut self.hint_hooks_enabled);
+    }
+}
+
+/// Locations already checked on the server, plus the offline checks that are about to be sent
+fn get_known_checked_locations(client: &Client<ModModeData>) -> HashSet<i64> {
+    let mut known: HashSet<i64> = client.checked_locations().map(|loc| loc.id()).collect();
+    if let Ok(offline_checks) = item_sync::OFFLINE_CHECKS.lock() {
+        known.extend(offline_checks.iter().copied());
+    }
+    match item_sync::read_save_data(client) {
+        Ok(sync_data) => known.extend(sync_data.offline_checks.iter().copied()),
+        Err(err) => log::error!("Error getting sync data: {}", err),
+    }
+    known
 }
 
 /// This is run when a there is a valid connection to a room.
 pub fn run_setup(client: &mut Client<ModModeData>) -> Result<(), Box<dyn Error>> {
     log::info!("Running setup");
     hook::rewrite_mode_table();
+    if let ModModeData::Normal(mapping) = client.slot_data() {
+        game_manager::scale_shop_prices(mapping.shop_price_scale);
+    }
     // Secret Mission Scouts
     archipelago_utilities::run_scouts(
         client.scout_locations(get_secret_missions(client), CreateAsHint::No),
@@ -311,23 +442,96 @@ pub fn run_setup(client: &mut Client<ModModeData>) -> Result<(), Box<dyn Error>>
             log::debug!("Gun checks: {:?}", gun_checks);
             locations_to_scout.extend(&gun_checks);
         }
-        // if AutoHint::All == mapping.auto_skill_hints {
-        //     // locations_to_hint.extend(
-        //     //     generated_locations::ITEM_MISSION_MAP
-        //     //         .iter()
-        //     //         .filter(|(k, _)| k.contains("Purple Orb") || k.contains("Blue Orb"))
-        //     //         .map(|(k, _)| &client.this_game().location_by_name(*k).unwrap().id())
-        //     //         .collect::<Vec<i64>>())
-        // }
+        if mapping.shop_skill_checks {
+            let skill_checks = generated_locations::ITEM_MISSION_MAP
+                .iter()
+                .filter(|(k, _)| {
+                    constants::get_melee_names(mapping.character)
+                        .iter()
+                        .any(|weapon| k.starts_with(&format!("Purchase {} - ", weapon)))
+                })
+                .filter_map(|(&k, _)| match client.this_game().location_by_name(k) {
+                    Some(loc) => Some(loc.id()),
+                    None => {
+                        log::warn!("Skill check {} isn't in the server's datapackage", k);
+                        None
+                    }
+                })
+                .collect::<Vec<i64>>();
+            locations_to_scout.extend(&skill_checks);
+        }
         if !locations_to_scout.is_empty() {
             archipelago_utilities::run_scouts(
                 client.scout_locations(locations_to_scout, CreateAsHint::No),
             );
         }
     }
+    validate_bp_floors_per_check();
+    if let ModModeData::Normal(mapping) = client.slot_data()
+        && mapping.enemy_randomizer_seed.is_some()
+    {
+        enemy_randomizer::install_hook();
+    }
+    auto_hint::on_connect(client);
     Ok(())
 }
 
+/// Floor checks only exist every 100 floors, round anything else up to the next one
+fn validate_bp_floors_per_check() {
+    let Ok(mut mapping) = MAPPING.write() else {
+        return;
+    };
+    let Some(mapping) = mapping.as_mut() else {
+        return;
+    };
+    let floors = mapping.bp_floors_per_check;
+    if floors % 100 == 0 {
+        return;
+    }
+    let rounded = floors.div_ceil(100).min(99) * 100;
+    log::warn!(
+        "Bloody Palace floors per check must be a multiple of 100, using {} instead of {}",
+        rounded,
+        floors
+    );
+    overlay::add_message(OverlayMessage::new(
+        vec![MessageSegment::new(
+            format!(
+                "Bloody Palace floors per check ({}) isn't a multiple of 100, using {}",
+                floors, rounded
+            ),
+            WHITE,
+        )],
+        Duration::from_secs(5),
+        0.0,
+        0.0,
+        MessageType::Notification,
+    ));
+    mapping.bp_floors_per_check = rounded;
+}
+
+fn install_hint_hooks(hint_hooks_installed: &mut bool, hint_hooks_enabled: &mut bool) {
+    if !*hint_hooks_installed {
+        unsafe {
+            match hint_game::create_hint_hooks() {
+                Ok(_) => {
+                    log::debug!("Created DMC3 Hint Hooks");
+                    *hint_hooks_installed = true;
+                }
+                Err(err) => {
+                    log::error!("Failed to create hint hooks: {:?}", err);
+                }
+            }
+        }
+    }
+    if *hint_hooks_installed && !*hint_hooks_enabled {
+        unsafe {
+            hint_game::enable_hint_hooks();
+            *hint_hooks_enabled = true;
+        }
+    }
+}
+
 fn disconnect(hooks_enabled: &mut bool, hint_hooks_enabled: &mut bool) {
     log::info!("Disconnecting and restoring game");
     if *hooks_enabled {
@@ -354,14 +558,18 @@ fn disconnect(hooks_enabled: &mut bool, hint_hooks_enabled: &mut bool) {
         }
     }
     MAPPING.write().unwrap().take(); // Clear mappings
+    goal::clear_status();
     *ARCHIPELAGO_DATA.write().unwrap() = ArchipelagoData::default(); // Reset Data (Probably not needed)
     hook::restore_mode_table();
+    game_manager::restore_shop_prices();
+    checklist::clear();
     log::info!("Game restored to default state");
 }
 
 fn handle_item_receive(
     client: &mut Client<ModModeData>,
     received_item: Location,
+    known_checked_locations: &mut HashSet<i64>,
 ) -> Result<(), Box<dyn Error>> {
     // See if there's an item!
     log::info!("Processing item: {}", received_item);
@@ -379,14 +587,35 @@ fn handle_item_receive(
     {
         Some(located_item) => {
             location_handler::edit_end_event(location_key); // Needed so a mission will end properly after picking up its trigger.
-            text_handler::replace_unused_with_text(archipelago_utilities::get_description(
+            text_handler::replace_unused_with_markup(&text_markup::item_found_message(
                 located_item,
             ));
             text_handler::CANCEL_TEXT.store(true, Ordering::SeqCst);
+            known_checked_locations.insert(located_item.location().id());
             if let Err(arch_err) = client.mark_checked(vec![located_item.location()]) {
                 log::error!("Failed to check location: {}", arch_err);
                 item_sync::add_offline_check(located_item.location().id());
             }
+            if located_item.sender() != located_item.receiver()
+                && overlay::should_notify_item(client, located_item)
+            {
+                let sent_msg: Vec<MessageSegment> = vec![
+                    MessageSegment::new("Sent ".to_string(), WHITE),
+                    MessageSegment::new(
+                        located_item.item().name().to_string(),
+                        overlay::get_color_for_item(located_item),
+                    ),
+                    MessageSegment::new(" to ".to_string(), WHITE),
+                    MessageSegment::new(located_item.receiver().alias().to_string(), YELLOW),
+                ];
+                overlay::add_message(OverlayMessage::new(
+                    sent_msg,
+                    Duration::from_secs(3),
+                    0.0,
+                    0.0,
+                    MessageType::ItemSent,
+                ));
+            }
             let name = located_item.item().name();
             let in_game_id = if located_item.sender() == located_item.receiver() {
                 located_item.item().as_item_id() as u32
@@ -408,47 +637,12 @@ fn handle_item_receive(
         }
         None => Err(anyhow::anyhow!("Location not found: {}", location_key))?,
     }
+    checklist::refresh(client, received_item.mission);
     // Add to checked locations
-    if has_reached_goal(client) {
-        client.set_status(ClientStatus::Goal)?
-    }
+    goal::check_goal(client)?;
     Ok(())
 }
 
-fn has_reached_goal(client: &mut Client<ModModeData>) -> bool {
-    let mut chk = client.checked_locations();
-    match client.slot_data() {
-        ModModeData::HintGame(_) => {
-            log::error!("Trying to check for goal in HintGame mode");
-            false
-        }
-        ModModeData::Normal(mapping) => {
-            match mapping.goal {
-                Goal::Standard => chk.any(|loc| loc.name() == "Mission #20 Complete"),
-                Goal::All => {
-                    for i in 1..20 {
-                        // If we are missing a mission complete check then we cannot goal
-                        if !chk.any(|loc| loc.name() == format!("Mission #{} Complete", i).as_str())
-                        {
-                            return false;
-                        }
-                    }
-                    // If we have them all, goal
-                    true
-                }
-                Goal::RandomOrder => {
-                    if let Some(order) = &mapping.mission_order {
-                        return chk.any(|loc| {
-                            loc.name() == format!("Mission #{} Complete", order[19]).as_str()
-                        });
-                    }
-                    false
-                }
-            }
-        }
-    }
-}
-
 pub fn handle_received_items_packet(
     index: usize,
     client: &mut Client<ModModeData>,
@@ -460,10 +654,14 @@ pub fn handle_received_items_packet(
         }
         match ARCHIPELAGO_DATA.write() {
             Ok(mut data) => {
+                if !data.starting_state_applied {
+                    apply_starting_state(&mut data, client);
+                }
                 for item in client.received_items().iter() {
                     // Display overlay text if we're not at the main menu
                     if !utilities::is_on_main_menu()
                         && item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize
+                        && overlay::should_notify_item(client, item.as_ref())
                     {
                         let rec_msg: Vec<MessageSegment> = vec![
                             MessageSegment::new("Received ".to_string(), WHITE),
@@ -479,123 +677,17 @@ pub fn handle_received_items_packet(
                             Duration::from_secs(3),
                             0.0,
                             0.0,
-                            MessageType::Notification,
+                            MessageType::ItemReceived,
                         ));
                     }
 
-                    match item.item().as_item_id() {
-                        0x01..0x04 => {
-                            if item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize {
-                                let orbs = match item.item().as_item_id() {
-                                    1 => 1000,
-                                    2 => 2500,
-                                    3 => 5000,
-                                    _ => unreachable!(),
-                                };
-                                game_manager::give_red_orbs(orbs);
-                            }
-                        }
-                        0x07 => {
-                            data.add_blue_orb();
-                            game_manager::give_hp(constants::ONE_ORB);
-                        }
-                        0x08 => {
-                            data.add_purple_orb();
-                            game_manager::give_magic(constants::ONE_ORB, &data);
-                        }
-                        0x10..0x14 => {
-                            // Don't add duplicate consumables
-                            if item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize {
-                                game_manager::add_consumable(item.item());
-                            }
-                        }
-                        0x19 => {
-                            // Awakened Rebellion
-                            data.add_dt();
-                            game_manager::give_magic(constants::ONE_ORB * 3.0, &data);
-                        }
-                        0x22..0x24 => {
-                            // Quicksilver and Doppel
-                        }
-                        0x24..0x3A => {
-                            // For key items
-                            log::debug!("Setting newly acquired key items");
-                            match MISSION_ITEM_MAP.get(&(get_mission())) {
-                                None => {} // No items for the mission
-                                Some(item_list) => {
-                                    if item_list.contains(&&*item.item().name()) {
-                                        game_manager::set_item(&item.item().name(), true, true);
-                                    }
-                                }
-                            }
-                        }
-                        0x3A..0x53 => {
-                            // For skills
-                            if let ModModeData::Normal(mapping) = client.slot_data()
-                                && mapping.randomize_skills
-                            {
-                                skill_manager::add_skill(item.item().id() as usize, &mut data);
-                                skill_manager::set_skills(&data); // Hacky...
-                            }
-                        }
-                        0x53..0x58 => {
-                            // Gun Levels
-                            data.add_gun_level((item.item().id() - 0x53) as usize);
-                        }
-                        0x60..0x64 => {
-                            // Style Handling
-                            let style = match item.item().id() {
-                                0x60 => Style::Trickster,
-                                0x61 => Style::Swordmaster,
-                                0x62 => Style::Gunslinger,
-                                0x63 => Style::Royalguard,
-                                _ => unreachable!(),
-                            };
-                            data.add_style_level(style);
-                            if item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize {
-                                game_manager::apply_style_levels(style);
-                            }
-                        }
-                        // Weapons
-                        0x16..=0x18 => {
-                            // Rebellion, Cerberus, Agni and Rudra
-                        }
-                        0x1A..=0x1B => {
-                            // Nevan, Beowulf
-                        }
-                        0x1C..=0x21 => {
-                            // All guns
-                            if let ModModeData::Normal(mapping) = client.slot_data()
-                                && mapping.shop_gun_checks
-                                && mapping.auto_gun_hints == AutoHint::Obtained
-                            {
-                                let ids: Vec<_> = [2, 3]
-                                    .into_iter()
-                                    .map(|lvl| {
-                                        client
-                                            .this_game()
-                                            .location_by_name(format!(
-                                                "Purchase {} Level {}",
-                                                item.item().name(),
-                                                lvl
-                                            ))
-                                            .unwrap()
-                                            .id()
-                                    })
-                                    .collect();
-
-                                TX_HINT.get().unwrap().send(ids)?;
-                            }
-                        }
-                        _ => {
-                            log::warn!(
-                                "Unhandled item ID: {} ({:#X})",
-                                item.item().name(),
-                                item.item().id()
-                            )
-                        }
-                    }
-
+                    apply_item(
+                        &mut data,
+                        client,
+                        item.item().id() as u32,
+                        &item.item().name(),
+                        item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize,
+                    );
                     data.add_item(item.item().name().into());
                     if item.index() >= CURRENT_INDEX.load(Ordering::SeqCst) as usize {
                         CURRENT_INDEX.store((item.index() + 1) as i64, Ordering::SeqCst);
@@ -609,3 +701,181 @@ pub fn handle_received_items_packet(
     }
     Ok(())
 }
+
+/// Set when a new game is started, so the starter items are given out for real exactly once
+pub(crate) static GRANT_STARTER_ITEMS: AtomicBool = AtomicBool::new(false);
+
+/// Everything the player has before receiving any items, needs to be redone whenever the data is reset
+fn apply_starting_state(data: &mut ArchipelagoData, client: &Client<ModModeData>) {
+    data.starting_state_applied = true;
+    let is_new = GRANT_STARTER_ITEMS.swap(false, Ordering::SeqCst);
+    let ModModeData::Normal(mapping) = client.slot_data() else {
+        return;
+    };
+    // Vergil has his DT from the start unless it has to be found
+    if mapping.character == Character::Vergil && !mapping.devil_trigger_mode {
+        data.dt_unlocked = true;
+    }
+    for name in mapping.starter_items.iter() {
+        match constants::get_item_id(name) {
+            Some(id) => {
+                log::debug!("Applying starter item: {}", name);
+                apply_item(data, client, id, name, is_new);
+                data.add_item(name.clone());
+            }
+            None => {
+                log::error!("Unknown starter item: {}", name);
+            }
+        }
+    }
+}
+
+/// Apply a single item to the stored data, `is_new` controls whether one time effects (orbs, consumables) happen
+fn apply_item(
+    data: &mut ArchipelagoData,
+    client: &Client<ModModeData>,
+    id: u32,
+    name: &str,
+    is_new: bool,
+) {
+    match id {
+        0x01..0x04 => {
+            if is_new {
+                let slot_values = match client.slot_data() {
+                    ModModeData::Normal(mapping) => mapping.red_orb_filler_values.as_deref(),
+                    ModModeData::HintGame(_) => None,
+                };
+                if let Some(orbs) = constants::get_red_orb_filler_amount(id, slot_values) {
+                    game_manager::give_red_orbs(orbs);
+                }
+            }
+        }
+        0x07 => {
+            data.add_blue_orb();
+            game_manager::give_hp(constants::ONE_ORB);
+        }
+        0x08 => {
+            data.add_purple_orb();
+            game_manager::give_magic(constants::ONE_ORB, data);
+        }
+        0x10..0x14 => {
+            // Don't add duplicate consumables
+            if is_new {
+                game_manager::add_consumable(id, name);
+            }
+        }
+        0x19 => {
+            // Awakened Rebellion
+            data.add_dt();
+            game_manager::give_magic(constants::ONE_ORB * 3.0, data);
+        }
+        0x22..0x24 => {
+            // Quicksilver and Doppel, extra copies only level them up with progressive styles on
+            let style = match id {
+                0x22 => Style::Quicksilver,
+                0x23 => Style::Doppelganger,
+                _ => unreachable!(),
+            };
+            let progressive = match client.slot_data() {
+                ModModeData::Normal(mapping) => mapping.progressive_styles,
+                ModModeData::HintGame(_) => false,
+            };
+            if data.get_style_level(style) == 0 || progressive {
+                data.add_style_level(style);
+                if is_new {
+                    game_manager::set_item(name, true, false);
+                    game_manager::apply_style_levels(style);
+                }
+            }
+        }
+        0x24..0x3A => {
+            // For key items
+            log::debug!("Setting newly acquired key items");
+            match MISSION_ITEM_MAP.get(&(get_mission())) {
+                None => {} // No items for the mission
+                Some(item_list) => {
+                    if item_list.contains(&name) {
+                        game_manager::set_item(name, true, true);
+                    }
+                }
+            }
+        }
+        0x3A..0x53 => {
+            // For skills
+            if let ModModeData::Normal(mapping) = client.slot_data()
+                && mapping.randomize_skills
+            {
+                skill_manager::add_skill(id as usize, data);
+                skill_manager::set_skills(data); // Hacky...
+            }
+        }
+        0x53..0x58 => {
+            // Gun Levels
+            data.add_gun_level((id - 0x53) as usize);
+        }
+        0x60..0x64 => {
+            // Style Handling
+            let style = match id {
+                0x60 => Style::Trickster,
+                0x61 => Style::Swordmaster,
+                0x62 => Style::Gunslinger,
+                0x63 => Style::Royalguard,
+                _ => unreachable!(),
+            };
+            data.add_style_level(style);
+            if is_new {
+                game_manager::apply_style_levels(style);
+            }
+        }
+        0x64 => {
+            // Dark Slayer sits in Trickster's slot
+            data.add_style_level(Style::Trickster);
+            if is_new {
+                game_manager::apply_style_levels(Style::Trickster);
+            }
+        }
+        0x65..0x68 => {
+            // Vergil's weapons
+            if is_new {
+                game_manager::add_vergil_weapon(name);
+            }
+            auto_hint::on_item_received(client, name);
+        }
+        0x68..0x6E => {
+            // Difficulty unlocks
+            match id {
+                0x68 => data.add_progressive_difficulty(),
+                0x69 => data.add_difficulty(Difficulty::Easy),
+                0x6A => data.add_difficulty(Difficulty::Hard),
+                0x6B => data.add_difficulty(Difficulty::VeryHard),
+                0x6C => data.add_difficulty(Difficulty::DanteMustDie),
+                0x6D => data.add_difficulty(Difficulty::HeavenOrHell),
+                _ => unreachable!(),
+            }
+            if let ModModeData::Normal(mapping) = client.slot_data()
+                && mapping.progressive_difficulty
+            {
+                hook::apply_difficulty_unlocks(mapping, data);
+            }
+        }
+        0x70..0x84 => {
+            // Mission keys
+            data.add_mission_key(id - 0x6F);
+        }
+        0x84..0x8C => {
+            // Costumes, in the same order as the costume flags
+            data.add_costume(id - 0x84);
+            if let ModModeData::Normal(mapping) = client.slot_data() {
+                hook::apply_costume_unlocks(mapping, data);
+            }
+        }
+        // Weapons
+        0x16..=0x18 | 0x1A..=0x1B | 0x1C..=0x21 => {
+            // Melee weapons and guns
+            auto_hint::on_item_received(client, name);
+        }
+        _ => {
+            log::warn!("Unhandled item ID: {} ({:#X})", name, id)
+        }
+    }
+}
@@ -0,0 +1,216 @@
+use crate::AP_CORE;
+use crate::constants::{GUN_NAMES, get_melee_names, get_weapon_id};
+use crate::data::generated_locations;
+use crate::hint_game::TX_HINT;
+use crate::mapping::{AutoHint, Mapping, ModModeData};
+use crate::skill_manager;
+use archipelago_rs::Client;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex};
+
+/// Locations we've already asked to hint, so the same hint isn't sent over and over
+static HINTED_LOCATIONS: LazyLock<Mutex<HashSet<i64>>> =
+    LazyLock::new(|| Mutex::new(HashSet::new()));
+/// Set once the shop has been hinted, reset when a new room is loaded
+static SHOP_VISIT_HINTED: AtomicBool = AtomicBool::new(false);
+
+const ORB_SLOTS: [(&str, u32); 2] = [("Blue Orb", 6), ("Purple Orb", 7)];
+
+/// Run upon connecting, handles the All and Obtained modes
+pub(crate) fn on_connect(client: &Client<ModModeData>) {
+    if let Ok(mut hinted) = HINTED_LOCATIONS.lock() {
+        hinted.clear();
+    }
+    SHOP_VISIT_HINTED.store(false, Ordering::SeqCst);
+    let ModModeData::Normal(mapping) = client.slot_data() else {
+        return;
+    };
+    let mut locations = vec![];
+    if orb_hints_enabled(mapping) && mapping.auto_orb_hints == AutoHint::All {
+        locations.extend(get_orb_slots());
+    }
+    for gun in GUN_NAMES.iter() {
+        if gun_hints_enabled(mapping)
+            && (mapping.auto_gun_hints == AutoHint::All
+                || (mapping.auto_gun_hints == AutoHint::Obtained
+                    && owns_weapon(client, mapping, gun)))
+        {
+            locations.extend(get_gun_slots(gun));
+        }
+    }
+    for weapon in get_melee_names(mapping.character).iter() {
+        if skill_hints_enabled(mapping)
+            && (mapping.auto_skill_hints == AutoHint::All
+                || (mapping.auto_skill_hints == AutoHint::Obtained
+                    && owns_weapon(client, mapping, weapon)))
+        {
+            locations.extend(get_skill_slots(weapon));
+        }
+    }
+    send_hints(client, locations);
+}
+
+/// Handles the Obtained mode, hinting the upgrades for a weapon once it's been received
+pub(crate) fn on_item_received(client: &Client<ModModeData>, item_name: &str) {
+    let ModModeData::Normal(mapping) = client.slot_data() else {
+        return;
+    };
+    if GUN_NAMES.contains(&item_name)
+        && gun_hints_enabled(mapping)
+        && mapping.auto_gun_hints == AutoHint::Obtained
+    {
+        send_hints(client, get_gun_slots(item_name));
+    }
+    if get_melee_names(mapping.character).contains(&item_name)
+        && skill_hints_enabled(mapping)
+        && mapping.auto_skill_hints == AutoHint::Obtained
+    {
+        send_hints(client, get_skill_slots(item_name));
+    }
+}
+
+/// Handles the Current mode, hinting whatever can be bought right now. Only done once per room
+pub(crate) fn on_shop_visit() {
+    if SHOP_VISIT_HINTED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    let Ok(core) = AP_CORE.get().unwrap().lock() else {
+        return;
+    };
+    let Some(client) = core.connection.client() else {
+        return;
+    };
+    let ModModeData::Normal(mapping) = client.slot_data() else {
+        return;
+    };
+    let checked: HashSet<String> = client
+        .checked_locations()
+        .map(|loc| loc.name().to_string())
+        .collect();
+    let mut locations = vec![];
+    // Orbs don't have anything to obtain, so Obtained acts the same as Current
+    if orb_hints_enabled(mapping)
+        && matches!(
+            mapping.auto_orb_hints,
+            AutoHint::Current | AutoHint::Obtained
+        )
+    {
+        for (orb, count) in ORB_SLOTS {
+            if let Some(next) = (1..=count)
+                .map(|i| format!("Purchase {} #{}", orb, i))
+                .find(|name| !checked.contains(name))
+            {
+                locations.push(next);
+            }
+        }
+    }
+    if gun_hints_enabled(mapping) && mapping.auto_gun_hints == AutoHint::Current {
+        for gun in GUN_NAMES.iter() {
+            if owns_weapon(client, mapping, gun)
+                && let Some(next) = get_gun_slots(gun)
+                    .into_iter()
+                    .find(|name| !checked.contains(name))
+            {
+                locations.push(next);
+            }
+        }
+    }
+    if skill_hints_enabled(mapping) && mapping.auto_skill_hints == AutoHint::Current {
+        for weapon in get_melee_names(mapping.character).iter() {
+            if !owns_weapon(client, mapping, weapon) {
+                continue;
+            }
+            for skill in skill_manager::get_skill_names_for_weapon(weapon) {
+                let prerequisite_met =
+                    skill_manager::get_skill_prerequisite(skill).is_none_or(|prerequisite| {
+                        checked.contains(&skill_manager::get_purchase_location_name(prerequisite))
+                    });
+                if prerequisite_met {
+                    locations.push(skill_manager::get_purchase_location_name(skill));
+                }
+            }
+        }
+    }
+    send_hints(client, locations);
+}
+
+pub(crate) fn reset_shop_visit() {
+    SHOP_VISIT_HINTED.store(false, Ordering::SeqCst);
+}
+
+fn orb_hints_enabled(mapping: &Mapping) -> bool {
+    mapping.shop_orb_checks && mapping.auto_orb_hints != AutoHint::None
+}
+
+fn gun_hints_enabled(mapping: &Mapping) -> bool {
+    mapping.shop_gun_checks && mapping.auto_gun_hints != AutoHint::None
+}
+
+fn skill_hints_enabled(mapping: &Mapping) -> bool {
+    mapping.randomize_skills
+        && mapping.shop_skill_checks
+        && mapping.auto_skill_hints != AutoHint::None
+}
+
+/// Either a starting weapon or one that's been received
+fn owns_weapon(client: &Client<ModModeData>, mapping: &Mapping, weapon: &str) -> bool {
+    let weapon_id = get_weapon_id(weapon);
+    [
+        mapping.start_melee,
+        mapping.start_second_melee,
+        mapping.start_gun,
+        mapping.start_second_gun,
+    ]
+    .contains(&weapon_id)
+        || client
+            .received_items()
+            .iter()
+            .any(|item| item.item().name() == weapon)
+}
+
+fn get_orb_slots() -> Vec<String> {
+    generated_locations::ITEM_MISSION_MAP
+        .keys()
+        .filter(|k| k.starts_with("Purchase Blue Orb") || k.starts_with("Purchase Purple Orb"))
+        .map(|k| k.to_string())
+        .collect()
+}
+
+fn get_gun_slots(gun: &str) -> Vec<String> {
+    [2, 3]
+        .iter()
+        .map(|lvl| format!("Purchase {} Level {}", gun, lvl))
+        .collect()
+}
+
+fn get_skill_slots(weapon: &str) -> Vec<String> {
+    skill_manager::get_skill_names_for_weapon(weapon)
+        .iter()
+        .map(|skill| skill_manager::get_purchase_location_name(skill))
+        .collect()
+}
+
+/// Send off hints for any of the locations that are unchecked and haven't been hinted yet
+fn send_hints(client: &Client<ModModeData>, location_names: Vec<String>) {
+    let unchecked: HashSet<i64> = client.unchecked_locations().map(|loc| loc.id()).collect();
+    let Ok(mut hinted) = HINTED_LOCATIONS.lock() else {
+        log::error!("Failed to get hinted locations");
+        return;
+    };
+    let ids: Vec<i64> = location_names
+        .iter()
+        .filter_map(|name| client.this_game().location_by_name(name.as_str()))
+        .map(|loc| loc.id())
+        .filter(|id| unchecked.contains(id) && hinted.insert(*id))
+        .collect();
+    if ids.is_empty() {
+        return;
+    }
+    log::debug!("Auto hinting {} locations", ids.len());
+    if let Some(tx) = TX_HINT.get()
+        && let Err(err) = tx.send(ids)
+    {
+        log::error!("Failed to send hints: {}", err);
+    }
+}
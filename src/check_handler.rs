@@ -1,10 +1,10 @@
 use crate::constants::{Coordinates, Difficulty, EMPTY_COORDINATES, Rank};
 use crate::data::generated_locations;
 use crate::game_manager::{ARCHIPELAGO_DATA, get_mission, set_item, with_session_read};
-use crate::mapping::MAPPING;
+use crate::mapping::{MAPPING, Mapping};
 use crate::ui::text_handler;
 use crate::utilities::{DMC3_ADDRESS, get_inv_address};
-use crate::{constants, create_hook, game_manager, location_handler};
+use crate::{auto_hint, constants, create_hook, game_manager, location_handler};
 use minhook::{MH_STATUS, MinHook};
 use randomizer_utilities::read_data_from_address;
 use std::cmp::PartialEq;
@@ -109,6 +109,9 @@ pub fn item_non_event(item_struct: usize) {
                             loc,
                             location_handler::get_mapped_item_id(location_name).unwrap(),
                         );
+                        text_handler::set_display_graphic(location_handler::get_display_graphic(
+                            location_name,
+                        ));
                     }
                     Err(err) => {
                         log::error!("{}", err);
@@ -132,12 +135,21 @@ pub fn item_non_event(item_struct: usize) {
             // Special check for red orbs
             // ORIG_ID.store(0, SeqCst);
         }
+        let orbs_before = game_manager::get_red_orbs();
         if let Some(original) = ORIGINAL_HANDLE_PICKUP.get() {
             original(item_struct);
         }
+        if item_id <= RED_ORB_MAX_ID
+            && let Some(mapping) = MAPPING.read().unwrap().as_ref()
+        {
+            game_manager::scale_red_orb_change(orbs_before, mapping.red_orb_multiplier);
+        }
     }
 }
 
+/// Red orbs are IDs 0x00 through 0x04
+const RED_ORB_MAX_ID: u8 = 0x04;
+
 fn is_valid_id(item_id: u32) -> bool {
     // Various orbs that I don't care about
     const INVALID_IDS: [u32; 13] = [
@@ -181,6 +193,9 @@ pub fn item_event(loc_chk_flg: usize, item_id: i16, unknown: i32) {
                         loc,
                         location_handler::get_mapped_item_id(location_name).unwrap(),
                     );
+                    text_handler::set_display_graphic(location_handler::get_display_graphic(
+                        location_name,
+                    ));
                 }
                 Err(err) => {
                     log::error!("Couldn't find location (Event): {}", err);
@@ -230,11 +245,12 @@ pub fn mission_complete_check(cuid_result: usize, ranking: i32) -> i32 {
             final_ranking
         );
         if let Some(mapping) = MAPPING.read().unwrap().as_ref() {
+            let clear_difficulty = game_manager::get_required_clear_difficulty(mapping);
             // For SS Rank specific checks
 
             if mapping.enabled_ss_rank
                 && rank == Rank::SS
-                && (difficulty >= mapping.mission_clear_difficulty || !mapping.check_ss_difficulty)
+                && (difficulty >= clear_difficulty || !mapping.check_ss_difficulty)
             {
                 send_off_location_coords(
                     Location {
@@ -249,8 +265,7 @@ pub fn mission_complete_check(cuid_result: usize, ranking: i32) -> i32 {
             }
 
             // Minimum rank and difficulty
-            if rank >= mapping.mission_clear_rank && difficulty >= mapping.mission_clear_difficulty
-            {
+            if rank >= mapping.mission_clear_rank && difficulty >= clear_difficulty {
                 send_off_location_coords(
                     Location {
                         location_type: LocationType::MissionComplete,
@@ -272,6 +287,7 @@ pub const PURCHASE_ITEM_ADDR: usize = 0x285bb0;
 pub static ORIGINAL_PURCHASE_ITEM: OnceLock<unsafe extern "C" fn(custom_gun: usize)> =
     OnceLock::new();
 pub fn purchase_item_check(ptr: usize) {
+    auto_hint::on_shop_visit();
     // Run original code, need consumables to still work
     if let Some(orig) = ORIGINAL_PURCHASE_ITEM.get() {
         unsafe {
@@ -321,6 +337,33 @@ pub fn purchase_item_check(ptr: usize) {
     }
 }
 
+/// Bloody Palace floors that end in a boss fight
+const BP_BOSS_FLOORS: [u16; 10] = [1000, 2000, 3000, 4000, 5000, 6000, 7000, 8000, 9000, 9999];
+const BP_LAST_FLOOR: u16 = 9999;
+
+/// Send off checks for any Bloody Palace milestones between the last floor and the new one
+pub(crate) fn send_bp_floor_checks(mapping: &Mapping, last_floor: u16, floor: u16) {
+    let floors_per_check = mapping.bp_floors_per_check;
+    ((last_floor + 1)..=floor.min(BP_LAST_FLOOR))
+        .filter(|f| {
+            (floors_per_check > 0 && f % floors_per_check == 0)
+                || (mapping.bp_boss_checks && BP_BOSS_FLOORS.contains(f))
+        })
+        .for_each(|f| {
+            log::debug!("Reached Bloody Palace floor {}", f);
+            send_off_location_coords(
+                Location {
+                    location_type: LocationType::BloodyPalaceFloor,
+                    item_id: 0,
+                    room: 0,
+                    mission: f as u32,
+                    coordinates: EMPTY_COORDINATES,
+                },
+                u32::MAX,
+            );
+        });
+}
+
 pub(crate) static TX_LOCATION: OnceLock<Sender<Location>> = OnceLock::new();
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -329,6 +372,7 @@ pub(crate) enum LocationType {
     MissionComplete,
     SSRank,
     PurchaseItem,
+    BloodyPalaceFloor,
 }
 
 #[derive(Debug, Clone, Copy)]
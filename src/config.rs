@@ -1,4 +1,7 @@
+use crate::ui::overlay::MessageType;
+use crate::ui::text_handler::DisplayItem;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::LazyLock;
 
 pub static CONFIG: LazyLock<Config> = LazyLock::new(|| {
@@ -29,10 +32,62 @@ pub struct Mods {
     pub disable_crimson_hooks: bool, // Stop Crimson hooks from being loaded
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OverlayAnchor {
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+}
+
+impl OverlayAnchor {
+    pub fn is_left(&self) -> bool {
+        matches!(self, OverlayAnchor::TopLeft | OverlayAnchor::BottomLeft)
+    }
+
+    pub fn is_bottom(&self) -> bool {
+        matches!(self, OverlayAnchor::BottomLeft | OverlayAnchor::BottomRight)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(default)]
+pub struct Overlay {
+    pub anchor: OverlayAnchor,    // Corner the notification stack is drawn from
+    pub text_scale: f32,          // Multiplier for the size of notification text
+    pub spacing_scale: f32,       // Multiplier for the gaps between overlay lines
+    pub max_visible_lines: usize, // Notifications shown at once (At least 1), the rest wait their turn
+    pub durations: HashMap<MessageType, f32>, // Seconds a message type stays up, overrides the default
+    pub only_for_me: bool,                    // Only notify about items sent to us
+    pub only_progression: bool,               // Only notify about progression items
+    pub hide_filler: bool,                    // Don't notify about filler items
+    pub suppress_red_orb_filler: bool,        // Don't notify about red orb filler
+}
+
+impl Default for Overlay {
+    fn default() -> Overlay {
+        Overlay {
+            anchor: OverlayAnchor::TopRight,
+            text_scale: 1.0,
+            spacing_scale: 1.0,
+            max_visible_lines: 8,
+            durations: HashMap::new(),
+            only_for_me: false,
+            only_progression: false,
+            hide_filler: false,
+            suppress_red_orb_filler: false,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct Config {
     pub connections: Connection,
     pub mods: Mods,
+    #[serde(default)]
+    pub item_graphics: HashMap<DisplayItem, u32>, // In-game item graphic to use for items the game has no graphic for
+    #[serde(default)]
+    pub overlay: Overlay,
 }
 
 impl Default for Config {
@@ -48,6 +103,8 @@ impl Default for Config {
                 disable_ddmk_hooks: false,
                 disable_crimson_hooks: false,
             },
+            item_graphics: HashMap::new(),
+            overlay: Overlay::default(),
         }
     }
 }
@@ -24,10 +24,15 @@ impl GameConfig for DMC3Config {
 
 pub const ITEM_MODE_TABLE: usize = 0x1B4534; // This is actually a constant, we like this one
 
+// Offsets into the active character's data (See utilities::get_active_char_address), DDMK's ActorData
+pub const EQUIPPED_STYLE_OFFSET: usize = 0x6338; // ActorData::style, in the game's internal style order
+pub const STYLE_LEVEL_OFFSET: usize = 0x6358; // ActorData::styleLevel, 0 is level 1
+
 pub const ONE_ORB: f32 = 1000.0; // One Blue/Purple orb is worth 1000 "points"
 pub const BASE_HP: f32 = 6.0 * ONE_ORB;
 pub const MAX_HP: f32 = 20000.0;
 pub const MAX_MAGIC: f32 = 10000.0;
+pub const VERGIL_BASE_MAGIC: f32 = 3.0 * ONE_ORB; // Vergil starts with his DT
 
 pub struct Item {
     pub id: u32,
@@ -35,8 +40,9 @@ pub struct Item {
     pub offset: Option<u8>, // Inventory offset
     pub category: ItemCategory,
     pub mission: Option<u32>, // Mission the key item is used in, typically the same that it is acquired in
-    pub _max_amount: Option<i32>, // Max amount of a consumable
-    pub _value: Option<i32>,  // Value of an orb, used only for red orbs
+    pub max_amount: Option<i32>, // Max amount of a consumable
+    pub value: Option<i32>,   // Value of an orb, used only for red orbs
+    pub refund: Option<i32>,  // Red orbs given instead of a consumable that's already at max_amount
 }
 
 #[derive(PartialEq)]
@@ -55,8 +61,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x38), // Note: I think this offset is wrong
         category: ItemCategory::RedOrb,
         mission: None,
-        _max_amount: Some(999999), // Is what fits on screen, could theoretically go up to MAX_INT
-        _value: Some(1),
+        max_amount: Some(999999), // Is what fits on screen, could theoretically go up to MAX_INT
+        value: Some(1),
+        refund: None,
     },
     Item {
         id: 0x01,
@@ -64,8 +71,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x38),
         category: ItemCategory::RedOrb,
         mission: None,
-        _max_amount: Some(999999),
-        _value: Some(5),
+        max_amount: Some(999999),
+        value: Some(5),
+        refund: None,
     },
     Item {
         id: 0x02,
@@ -73,8 +81,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x38),
         category: ItemCategory::RedOrb,
         mission: None,
-        _max_amount: Some(999999),
-        _value: Some(1),
+        max_amount: Some(999999),
+        value: Some(1),
+        refund: None,
     },
     Item {
         id: 0x03,
@@ -82,8 +91,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x38),
         category: ItemCategory::RedOrb,
         mission: None,
-        _max_amount: Some(999999),
-        _value: Some(100),
+        max_amount: Some(999999),
+        value: Some(100),
+        refund: None,
     },
     Item {
         id: 0x04,
@@ -91,8 +101,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x38),
         category: ItemCategory::RedOrb,
         mission: None,
-        _max_amount: Some(999999),
-        _value: Some(1000),
+        max_amount: Some(999999),
+        value: Some(1000),
+        refund: None,
     },
     Item {
         id: 0x05,
@@ -100,8 +111,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: Some(3),
-        _value: None,
+        max_amount: Some(3),
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x06,
@@ -109,8 +121,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: Some(99),
-        _value: None,
+        max_amount: Some(99),
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x07,
@@ -118,8 +131,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x08,
@@ -127,8 +141,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x09,
@@ -136,8 +151,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x45),
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: Some(4),
-        _value: None,
+        max_amount: Some(4),
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0A,
@@ -145,8 +161,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0B,
@@ -154,8 +171,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0C,
@@ -163,8 +181,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0D,
@@ -172,8 +191,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0E,
@@ -181,8 +201,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x0F,
@@ -190,8 +211,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x10,
@@ -199,8 +221,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x4C),
         category: ItemCategory::Consumable,
         mission: None,
-        _max_amount: Some(30),
-        _value: None,
+        max_amount: Some(30),
+        value: None,
+        refund: Some(3000),
     },
     Item {
         id: 0x11,
@@ -208,8 +231,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x4D),
         category: ItemCategory::Consumable,
         mission: None,
-        _max_amount: Some(30),
-        _value: None,
+        max_amount: Some(30),
+        value: None,
+        refund: Some(1000),
     },
     Item {
         id: 0x12,
@@ -217,8 +241,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x4E),
         category: ItemCategory::Consumable,
         mission: None,
-        _max_amount: Some(10),
-        _value: None,
+        max_amount: Some(10),
+        value: None,
+        refund: Some(3000),
     },
     Item {
         id: 0x13,
@@ -226,8 +251,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x4F),
         category: ItemCategory::Consumable,
         mission: None,
-        _max_amount: Some(30),
-        _value: None,
+        max_amount: Some(30),
+        value: None,
+        refund: Some(10000),
     },
     Item {
         id: 0x14,
@@ -235,8 +261,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x15,
@@ -244,8 +271,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: None,
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x16,
@@ -253,8 +281,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x52),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x17,
@@ -262,8 +291,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x53),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x18,
@@ -271,8 +301,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x54),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x19,
@@ -280,8 +311,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x55), // Offset is most likely wrong, but since we use this to give 3 runes, rather than an actual weapon, it should be fine
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1A,
@@ -289,8 +321,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x56),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1B,
@@ -298,8 +331,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x57),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1C,
@@ -307,8 +341,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x58),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1D,
@@ -316,8 +351,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x59),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1E,
@@ -325,8 +361,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5A),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x1F,
@@ -334,8 +371,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5B),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x20,
@@ -343,8 +381,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5C), // ??
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x21,
@@ -352,8 +391,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5D),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x22,
@@ -361,8 +401,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5E),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x23,
@@ -370,8 +411,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x5F),
         category: ItemCategory::Weapon,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x24,
@@ -379,8 +421,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x60),
         category: ItemCategory::Key,
         mission: Some(5),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x25,
@@ -388,8 +431,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x61),
         category: ItemCategory::Key,
         mission: Some(5),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x26,
@@ -397,8 +441,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x62),
         category: ItemCategory::Misc,
         mission: None,
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x27,
@@ -406,8 +451,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x63),
         category: ItemCategory::Key,
         mission: Some(5),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x28,
@@ -415,8 +461,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x64),
         category: ItemCategory::Key,
         mission: Some(6),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x29,
@@ -424,8 +471,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x65),
         category: ItemCategory::Key,
         mission: Some(6),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2A,
@@ -433,8 +481,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x66),
         category: ItemCategory::Key,
         mission: Some(6),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2B,
@@ -442,8 +491,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x67),
         category: ItemCategory::Key,
         mission: Some(7),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2C,
@@ -451,8 +501,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x68),
         category: ItemCategory::Key,
         mission: Some(7),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2D,
@@ -460,8 +511,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x69),
         category: ItemCategory::Key,
         mission: Some(7),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2E,
@@ -469,8 +521,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6A),
         category: ItemCategory::Key,
         mission: Some(8),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x2F,
@@ -478,8 +531,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6B),
         category: ItemCategory::Key,
         mission: Some(9),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x30,
@@ -487,8 +541,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6C),
         category: ItemCategory::Key,
         mission: Some(10),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x31,
@@ -496,8 +551,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6D),
         category: ItemCategory::Key,
         mission: Some(10),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x32,
@@ -505,8 +561,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6E),
         category: ItemCategory::Key,
         mission: Some(12),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x33,
@@ -514,8 +571,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x6F),
         category: ItemCategory::Key,
         mission: Some(13),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x34,
@@ -523,8 +581,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x70),
         category: ItemCategory::Key,
         mission: Some(15),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x35,
@@ -532,8 +591,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x71),
         category: ItemCategory::Key,
         mission: Some(15),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x36,
@@ -541,8 +601,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x72),
         category: ItemCategory::Key,
         mission: Some(15),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x37,
@@ -550,8 +611,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x73),
         category: ItemCategory::Key,
         mission: Some(16),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x38,
@@ -559,8 +621,9 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x74),
         category: ItemCategory::Key,
         mission: Some(16),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
     Item {
         id: 0x39,
@@ -568,11 +631,50 @@ pub(crate) const ALL_ITEMS: [Item; 58] = [
         offset: Some(0x75),
         category: ItemCategory::Key,
         mission: Some(19),
-        _max_amount: None,
-        _value: None,
+        max_amount: None,
+        value: None,
+        refund: None,
     },
 ];
 
+/// Items that only unlock something in the game's menus, they don't have an inventory slot
+pub(crate) const UNLOCK_ITEMS: [(u32, &str); 34] = [
+    (0x68, "Progressive Difficulty"),
+    (0x69, "Unlock Easy"),
+    (0x6A, "Unlock Hard"),
+    (0x6B, "Unlock Very Hard"),
+    (0x6C, "Unlock Dante Must Die"),
+    (0x6D, "Unlock Heaven or Hell"),
+    (0x70, "Mission #1 Key"),
+    (0x71, "Mission #2 Key"),
+    (0x72, "Mission #3 Key"),
+    (0x73, "Mission #4 Key"),
+    (0x74, "Mission #5 Key"),
+    (0x75, "Mission #6 Key"),
+    (0x76, "Mission #7 Key"),
+    (0x77, "Mission #8 Key"),
+    (0x78, "Mission #9 Key"),
+    (0x79, "Mission #10 Key"),
+    (0x7A, "Mission #11 Key"),
+    (0x7B, "Mission #12 Key"),
+    (0x7C, "Mission #13 Key"),
+    (0x7D, "Mission #14 Key"),
+    (0x7E, "Mission #15 Key"),
+    (0x7F, "Mission #16 Key"),
+    (0x80, "Mission #17 Key"),
+    (0x81, "Mission #18 Key"),
+    (0x82, "Mission #19 Key"),
+    (0x83, "Mission #20 Key"),
+    (0x84, "Coatless DMC1 Dante Costume"),
+    (0x85, "Super Dante Costume"),
+    (0x86, "Sparda Costume"),
+    (0x87, "Super Sparda Costume"),
+    (0x88, "Coatless Vergil Costume"),
+    (0x89, "Super Vergil Costume"),
+    (0x8A, "Corrupt Vergil Costume"),
+    (0x8B, "Super Corrupt Vergil Costume"),
+];
+
 pub static ITEM_OFFSET_MAP: LazyLock<HashMap<&'static str, u8>> = LazyLock::new(|| {
     ALL_ITEMS
         .iter()
@@ -599,6 +701,8 @@ pub fn get_item_name(item_id: u32) -> &'static str {
             log::error!("No item found with id {:#X}", item_id);
             "Unknown"
         })
+    } else if let Some((_, name)) = UNLOCK_ITEMS.iter().find(|(id, _)| *id == item_id) {
+        name
     } else {
         ID_SKILL_MAP
             .get(&(item_id as usize))
@@ -610,6 +714,57 @@ pub fn get_item_name(item_id: u32) -> &'static str {
     }
 }
 
+/// Opposite of get_item_name, used for items that are given out by name
+pub fn get_item_id(item_name: &str) -> Option<u32> {
+    ITEM_MAP
+        .get_by_left(item_name)
+        .copied()
+        .or_else(|| {
+            UNLOCK_ITEMS
+                .iter()
+                .find(|(_, name)| *name == item_name)
+                .map(|(id, _)| *id)
+        })
+        .or_else(|| {
+            ID_SKILL_MAP
+                .iter()
+                .find(|(_, name)| **name == item_name)
+                .map(|(id, _)| *id as u32)
+        })
+}
+
+pub fn get_item_by_id(item_id: u32) -> Option<&'static Item> {
+    ALL_ITEMS.iter().find(|item| item.id == item_id)
+}
+
+/// What the Red Orb filler items (IDs 1-3) give if slot_data doesn't say otherwise.
+/// These aren't the pickups' values, filler is meant to be worth a lot more than a single orb
+const RED_ORB_FILLER_AMOUNTS: [i32; 3] = [1000, 2500, 5000];
+
+/// Amount of red orbs a filler item gives, slot_data's values take priority over the defaults
+pub fn get_red_orb_filler_amount(item_id: u32, slot_values: Option<&[i32]>) -> Option<i32> {
+    let index = (item_id as usize).checked_sub(1)?;
+    let default = *RED_ORB_FILLER_AMOUNTS.get(index)?;
+    Some(
+        slot_values
+            .and_then(|values| values.get(index).copied())
+            .unwrap_or(default),
+    )
+}
+
+/// Adds one of a consumable to a count, clamped to its max_amount.
+/// Returns the new count and the red orbs to refund if it was already full
+pub fn add_capped_consumable(item: &Item, count: u8) -> (u8, i32) {
+    let max = item
+        .max_amount
+        .map_or(u8::MAX, |max| max.clamp(0, u8::MAX as i32) as u8);
+    if count >= max {
+        (count, item.refund.unwrap_or_default())
+    } else {
+        (count + 1, 0)
+    }
+}
+
 pub fn get_items_by_category(category: ItemCategory) -> Vec<&'static str> {
     ALL_ITEMS
         .iter()
@@ -929,6 +1084,18 @@ pub static MELEE_NAMES: LazyLock<Vec<&'static str>> = LazyLock::new(|| {
     ])
 });
 
+/// Vergil's weapons, he doesn't have any guns
+pub static VERGIL_MELEE_NAMES: LazyLock<Vec<&'static str>> =
+    LazyLock::new(|| Vec::from(["Yamato", "Beowulf (Vergil)", "Force Edge"]));
+
+/// Melee weapons the given character can use
+pub fn get_melee_names(character: Character) -> &'static [&'static str] {
+    match character {
+        Character::Vergil => VERGIL_MELEE_NAMES.as_slice(),
+        _ => MELEE_NAMES.as_slice(),
+    }
+}
+
 pub fn get_weapon_id(weapon: &str) -> u8 {
     match weapon {
         "Rebellion" => 0,
@@ -941,6 +1108,9 @@ pub fn get_weapon_id(weapon: &str) -> u8 {
         "Artemis" => 7,
         "Spiral" => 8,
         "Kalina Ann" => 9,
+        "Yamato" => 11,
+        "Beowulf (Vergil)" => 12,
+        "Force Edge" => 13,
         "None" => 0xFF,
         _ => 0xFF,
     }
@@ -961,11 +1131,79 @@ impl Coordinates {
     }
 }
 
-#[derive(Copy, Clone, strum_macros::Display, strum_macros::FromRepr)]
-pub(crate) enum Character {
+#[derive(
+    Copy, Clone, Debug, Default, Serialize, PartialEq, strum_macros::Display, strum_macros::FromRepr,
+)]
+pub enum Character {
     // Values from DDMK, only care about Dante or Vergil though
+    #[default]
     Dante,
     _Bob,
     _Lady,
     Vergil,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn consumable_is_added_below_max() {
+        let vital_star = get_item_by_id(0x11).unwrap();
+        assert_eq!(add_capped_consumable(vital_star, 0), (1, 0));
+        assert_eq!(add_capped_consumable(vital_star, 29), (30, 0));
+    }
+
+    #[test]
+    fn consumable_at_max_is_refunded() {
+        let vital_star = get_item_by_id(0x11).unwrap();
+        assert_eq!(add_capped_consumable(vital_star, 30), (30, 1000));
+        assert_eq!(add_capped_consumable(vital_star, 40), (40, 1000));
+    }
+
+    #[test]
+    fn consumable_without_refund_gives_nothing() {
+        let blue_orb_fragment = get_item_by_id(0x09).unwrap();
+        assert_eq!(add_capped_consumable(blue_orb_fragment, 4), (4, 0));
+    }
+
+    #[test]
+    fn red_orb_filler_uses_defaults() {
+        assert_eq!(get_red_orb_filler_amount(0x01, None), Some(1000));
+        assert_eq!(get_red_orb_filler_amount(0x02, None), Some(2500));
+        assert_eq!(get_red_orb_filler_amount(0x03, None), Some(5000));
+    }
+
+    #[test]
+    fn red_orb_filler_prefers_slot_values() {
+        let slot_values = [300, 600];
+        assert_eq!(
+            get_red_orb_filler_amount(0x01, Some(&slot_values)),
+            Some(300)
+        );
+        assert_eq!(
+            get_red_orb_filler_amount(0x02, Some(&slot_values)),
+            Some(600)
+        );
+        // Missing slot values fall back to the default
+        assert_eq!(
+            get_red_orb_filler_amount(0x03, Some(&slot_values)),
+            Some(5000)
+        );
+    }
+
+    #[test]
+    fn unlock_items_round_trip() {
+        for (id, name) in UNLOCK_ITEMS {
+            assert_eq!(get_item_name(id), name);
+            assert_eq!(get_item_id(name), Some(id));
+        }
+    }
+
+    #[test]
+    fn red_orb_filler_ignores_other_items() {
+        assert_eq!(get_red_orb_filler_amount(0x00, None), None);
+        assert_eq!(get_red_orb_filler_amount(0x04, None), None);
+        assert_eq!(get_red_orb_filler_amount(0x10, None), None);
+    }
+}
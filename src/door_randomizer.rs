@@ -0,0 +1,178 @@
+use crate::game_manager::{get_mission, get_room, with_session_read};
+use crate::mapping::{MAPPING, Mapping, ModModeData};
+use crate::utilities;
+use archipelago_rs::{Client, DataStorageOperation};
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fs;
+use std::ptr::write;
+use std::sync::mpsc::Sender;
+use std::sync::{LazyLock, OnceLock, RwLock};
+
+/// Rooms entered in game, recorded and saved from the AP loop rather than the game thread
+pub(crate) static TX_ROOM_VISIT: OnceLock<Sender<i32>> = OnceLock::new();
+
+/// Every room entered so far, in the order they were first visited
+static VISITED_ROOMS: LazyLock<RwLock<Vec<i32>>> = LazyLock::new(|| RwLock::new(Vec::new()));
+/// The current and previous (mission, room), the previous room is where backtracking leads
+static ROOM_HISTORY: LazyLock<RwLock<RoomHistory>> =
+    LazyLock::new(|| RwLock::new(RoomHistory::default()));
+
+#[derive(Default, Debug)]
+struct RoomHistory {
+    current: Option<(u32, i32)>,
+    previous: Option<(u32, i32)>,
+}
+
+/// Visited rooms, stored locally so they survive a restart
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct DoorProgress {
+    visited_rooms: Vec<i32>,
+}
+
+fn get_progress_path(client: &Client<ModModeData>) -> Result<String, Box<dyn Error>> {
+    Ok(format!(
+        "{}{}",
+        randomizer_utilities::get_room_path(client)?,
+        "door_progress.json"
+    ))
+}
+
+/// Load the visited rooms for the AP room
+pub(crate) fn load_progress(client: &Client<ModModeData>) {
+    if let Ok(mut history) = ROOM_HISTORY.write() {
+        *history = RoomHistory::default();
+    }
+    let progress = get_progress_path(client)
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .and_then(|str| Ok(serde_json::from_str::<DoorProgress>(&str)?))
+        .unwrap_or_else(|err| {
+            log::debug!("No door progress loaded: {}", err);
+            DoorProgress::default()
+        });
+    match VISITED_ROOMS.write() {
+        Ok(mut visited) => *visited = progress.visited_rooms,
+        Err(err) => log::error!("Failed to set visited rooms: {}", err),
+    }
+}
+
+/// Record the room, then point its doors at wherever slot_data says they go
+pub(crate) fn on_room_loaded(mapping: &Mapping) {
+    let Some(connections) = &mapping.door_connections else {
+        return;
+    };
+    let (mission, room) = (get_mission(), get_room());
+    if let Ok(mut history) = ROOM_HISTORY.write()
+        && history.current != Some((mission, room))
+    {
+        // Only rooms from the same mission can be backtracked to
+        history.previous = history.current.filter(|(m, _)| *m == mission);
+        history.current = Some((mission, room));
+    }
+    let Some(event_table_addr) = utilities::get_event_address() else {
+        return;
+    };
+    for door in connections
+        .iter()
+        .filter(|door| door.mission == mission && door.room == room)
+    {
+        log::debug!(
+            "Redirecting door at {:#X} in room {} to room {}",
+            door.offset,
+            room,
+            door.target
+        );
+        unsafe {
+            write((event_table_addr + door.offset) as *mut i32, door.target);
+        }
+    }
+    if let Some(tx) = TX_ROOM_VISIT.get()
+        && let Err(err) = tx.send(room)
+    {
+        log::error!("Failed to send room visit: {}", err);
+    }
+}
+
+/// Add the room to the visited list, saving it locally and to data storage if it's new
+pub(crate) fn record_visit(
+    client: &mut Client<ModModeData>,
+    room: i32,
+) -> Result<(), Box<dyn Error>> {
+    let visited = {
+        let Ok(mut visited) = VISITED_ROOMS.write() else {
+            return Err("Failed to get visited rooms".into());
+        };
+        if visited.contains(&room) {
+            return Ok(());
+        }
+        visited.push(room);
+        visited.clone()
+    };
+    let progress = DoorProgress {
+        visited_rooms: visited,
+    };
+    fs::write(
+        get_progress_path(client)?,
+        serde_json::to_string(&progress)?,
+    )?;
+    let key = format!(
+        "dmc3_rooms_visited_{}_{}",
+        client.this_player().team(),
+        client.this_player().slot()
+    );
+    client.set(
+        key,
+        serde_json::Value::Null,
+        false,
+        vec![DataStorageOperation::Replace(serde_json::to_value(
+            &progress.visited_rooms,
+        )?)],
+    )?;
+    Ok(())
+}
+
+/// For when the player gets stuck. This isn't a warp, it makes every randomized door in the current room lead
+/// back to the room they came from (in the same mission), so they still have to walk through one.
+/// Returns the room the doors now lead to
+pub(crate) fn backtrack_doors() -> Result<i32, Box<dyn Error>> {
+    let Ok(mapping) = MAPPING.read() else {
+        return Err("Failed to get mappings".into());
+    };
+    let Some(connections) = mapping
+        .as_ref()
+        .and_then(|mapping| mapping.door_connections.as_ref())
+    else {
+        return Err("Door randomizer is not enabled".into());
+    };
+    let Some((mission, previous_room)) = ROOM_HISTORY
+        .read()
+        .ok()
+        .and_then(|history| history.previous)
+    else {
+        return Err("No room to go back to".into());
+    };
+    let Some(event_table_addr) = utilities::get_event_address() else {
+        return Err("Event table is not loaded".into());
+    };
+    let Ok(room) = with_session_read(|s| s.room) else {
+        return Err("Not in a mission".into());
+    };
+    let mut doors = connections
+        .iter()
+        .filter(|door| door.mission == mission && door.room == room)
+        .peekable();
+    if doors.peek().is_none() {
+        return Err(format!("No randomized doors in room {}", room).into());
+    }
+    for door in doors {
+        unsafe {
+            write((event_table_addr + door.offset) as *mut i32, previous_room);
+        }
+    }
+    log::info!(
+        "Doors in room {} now lead back to room {}",
+        room,
+        previous_room
+    );
+    Ok(previous_room)
+}
@@ -0,0 +1,323 @@
+use crate::create_hook;
+use crate::game_manager::get_room;
+use crate::mapping::{MAPPING, Mapping};
+use crate::utilities::{DMC3_ADDRESS, read_data_from_address};
+use minhook::{MH_STATUS, MinHook};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::ptr::write_unaligned;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, Mutex, OnceLock};
+
+/// Enemy IDs and exclusion rules, see data/enemies.json
+static ENEMY_TABLE: LazyLock<EnemyTable> = LazyLock::new(|| {
+    serde_json::from_str(include_str!("data/enemies.json")).expect("Invalid enemies.json")
+});
+
+#[derive(Deserialize, Debug)]
+pub(crate) struct EnemyTable {
+    enemies: Vec<EnemyData>,
+    /// Rooms that are left entirely alone
+    fixed_rooms: Vec<i32>,
+    /// Enemies that stay as they are in specific rooms only, for ones that are fine to swap elsewhere
+    #[serde(default)]
+    room_exclusions: HashMap<i32, Vec<u8>>,
+}
+
+#[derive(Deserialize, Debug)]
+struct EnemyData {
+    id: u8,
+    name: String,
+    /// Enemies only get swapped with others from the same pool. No pool means the enemy is
+    /// puzzle/key-critical everywhere (Arachne door gates, Enigma locks, chess board...) and never changed
+    pool: Option<String>,
+}
+
+impl EnemyTable {
+    fn is_excluded(&self, room: i32, id: u8) -> bool {
+        self.room_exclusions
+            .get(&room)
+            .is_some_and(|enemies| enemies.contains(&id))
+    }
+
+    fn get_pool(&self, id: u8) -> Option<&str> {
+        self.enemies
+            .iter()
+            .find(|enemy| enemy.id == id)
+            .and_then(|enemy| enemy.pool.as_deref())
+    }
+
+    fn get_pool_members(&self, pool: &str) -> Vec<u8> {
+        self.enemies
+            .iter()
+            .filter(|enemy| enemy.pool.as_deref() == Some(pool))
+            .map(|enemy| enemy.id)
+            .collect()
+    }
+
+    fn get_name(&self, id: u8) -> &str {
+        self.enemies
+            .iter()
+            .find(|enemy| enemy.id == id)
+            .map_or("Unknown", |enemy| enemy.name.as_str())
+    }
+}
+
+/// SplitMix64, kept local so the result for a seed never changes between builds or crate versions
+struct SpawnRng(u64);
+
+impl SpawnRng {
+    fn new(seed: u64, room: i32) -> Self {
+        SpawnRng(seed ^ (room as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    fn pick(&mut self, options: &[u8]) -> u8 {
+        options[(self.next() % options.len() as u64) as usize]
+    }
+}
+
+/// Randomizes a room's spawn list. Every enemy type in the room is swapped for one other type from its
+/// pool, so groups stay together. Fixed enemies, unknown IDs, fixed rooms and enemies excluded in the
+/// room are left as they are.
+/// Pure and deterministic, the same seed, room and spawns always give the same result
+pub(crate) fn shuffle_spawns(table: &EnemyTable, seed: u64, room: i32, spawns: &[u8]) -> Vec<u8> {
+    if table.fixed_rooms.contains(&room) {
+        return spawns.to_vec();
+    }
+    let mut rng = SpawnRng::new(seed, room);
+    let mut replacements: HashMap<u8, u8> = HashMap::new();
+    spawns
+        .iter()
+        .map(|&id| {
+            if table.is_excluded(room, id) {
+                return id;
+            }
+            let Some(pool) = table.get_pool(id) else {
+                return id;
+            };
+            *replacements
+                .entry(id)
+                .or_insert_with(|| rng.pick(&table.get_pool_members(pool)))
+        })
+        .collect()
+}
+
+/// Randomizes the spawn list for a room if the enemy randomizer is enabled
+fn randomize_room_spawns(mapping: &Mapping, room: i32, spawns: &mut [u8]) {
+    let Some(seed) = mapping.enemy_randomizer_seed else {
+        return;
+    };
+    let randomized = shuffle_spawns(&ENEMY_TABLE, seed, room, spawns);
+    for (original, new) in spawns.iter_mut().zip(randomized) {
+        if *original != new {
+            log::debug!(
+                "Replacing {} with {} in room {}",
+                ENEMY_TABLE.get_name(*original),
+                ENEMY_TABLE.get_name(new),
+                room
+            );
+            *original = new;
+        }
+    }
+}
+
+/// Sets up the enemies of the room being loaded, the set has the same header layout as the item spawn list
+const ENEMY_SET_ADDR: usize = 0x1b4b30;
+static ORIGINAL_ENEMY_SET: OnceLock<unsafe extern "C" fn(enemy_set: usize)> = OnceLock::new();
+const ENEMY_COUNT_OFFSET: usize = 0x06;
+const ENEMY_LIST_OFFSET: usize = 0x10;
+const ENEMY_ENTRY_SIZE: usize = 0x14;
+
+static HOOK_INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// A room's spawn list as the game had it, then as it was randomized
+type SavedSpawns = (Vec<u8>, Vec<u8>);
+
+/// Saved per room, in case the game reuses the already randomized list when the room is loaded again
+static ROOM_SPAWNS: LazyLock<Mutex<HashMap<i32, SavedSpawns>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// The game's own spawn list for a room, the saved one if what's loaded is what we randomized it to
+fn get_original_spawns(saved: Option<&SavedSpawns>, loaded: &[u8]) -> Vec<u8> {
+    match saved {
+        Some((original, randomized)) if randomized == loaded => original.clone(),
+        _ => loaded.to_vec(),
+    }
+}
+
+/// Only installed for seeds with the enemy randomizer on, stays installed since it checks the mapping anyway
+pub(crate) fn install_hook() {
+    if HOOK_INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    unsafe {
+        if let Err(err) = create_enemy_hook() {
+            log::error!("Failed to create enemy randomizer hook: {:?}", err);
+            return;
+        }
+        if let Err(err) = MinHook::enable_hook((*DMC3_ADDRESS + ENEMY_SET_ADDR) as *mut _) {
+            log::error!("Failed to enable {:#X} hook: {:?}", ENEMY_SET_ADDR, err);
+        }
+    }
+}
+
+unsafe fn create_enemy_hook() -> Result<(), MH_STATUS> {
+    unsafe {
+        create_hook!(
+            ENEMY_SET_ADDR,
+            load_enemy_set,
+            ORIGINAL_ENEMY_SET,
+            "Randomize room enemies"
+        );
+    }
+    Ok(())
+}
+
+fn load_enemy_set(enemy_set: usize) {
+    if enemy_set != 0
+        && let Ok(mapping) = MAPPING.read()
+        && let Some(mapping) = mapping.as_ref()
+        && let Ok(mut room_spawns) = ROOM_SPAWNS.lock()
+    {
+        let count = read_data_from_address::<u32>(enemy_set + ENEMY_COUNT_OFFSET) as usize;
+        let entry_addr = |i: usize| enemy_set + ENEMY_LIST_OFFSET + i * ENEMY_ENTRY_SIZE;
+        let loaded: Vec<u8> = (0..count)
+            .map(|i| u8::try_from(read_data_from_address::<u32>(entry_addr(i))).unwrap_or(u8::MAX))
+            .collect();
+        let room = get_room();
+        let original = get_original_spawns(room_spawns.get(&room), &loaded);
+        let mut spawns = original.clone();
+        randomize_room_spawns(mapping, room, &mut spawns);
+        for (i, (before, after)) in loaded.iter().zip(&spawns).enumerate() {
+            if before != after {
+                unsafe { write_unaligned(entry_addr(i) as *mut u32, *after as u32) }
+            }
+        }
+        room_spawns.insert(room, (original, spawns));
+    }
+    if let Some(original) = ORIGINAL_ENEMY_SET.get() {
+        unsafe { original(enemy_set) }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TEST_TABLE: &str = r#"{
+        "enemies": [
+            { "id": 0, "name": "Hell Pride 1", "pool": "lesser" },
+            { "id": 4, "name": "Hell Gluttony 1", "pool": "lesser" },
+            { "id": 8, "name": "Hell Lust 1", "pool": "lesser" },
+            { "id": 24, "name": "Hell Abyss", "pool": "greater" },
+            { "id": 25, "name": "Envy", "pool": "greater" },
+            { "id": 28, "name": "Arachne", "pool": "greater" },
+            { "id": 31, "name": "Enigma", "pool": null }
+        ],
+        "fixed_rooms": [7],
+        "room_exclusions": { "5": [28] }
+    }"#;
+
+    fn test_table() -> EnemyTable {
+        serde_json::from_str(TEST_TABLE).unwrap()
+    }
+
+    #[test]
+    fn enemy_table_parses() {
+        assert!(!ENEMY_TABLE.enemies.is_empty());
+    }
+
+    #[test]
+    fn same_seed_gives_same_spawns() {
+        let table = test_table();
+        let spawns = [0, 0, 4, 24, 28, 31];
+        for seed in 0..32 {
+            assert_eq!(
+                shuffle_spawns(&table, seed, 3, &spawns),
+                shuffle_spawns(&table, seed, 3, &spawns)
+            );
+        }
+    }
+
+    #[test]
+    fn replacements_stay_in_pool() {
+        let table = test_table();
+        let spawns = [0, 4, 8, 24, 25, 28];
+        for seed in 0..32 {
+            for (original, new) in spawns.iter().zip(shuffle_spawns(&table, seed, 3, &spawns)) {
+                assert_eq!(table.get_pool(*original), table.get_pool(new));
+            }
+        }
+    }
+
+    #[test]
+    fn groups_stay_together() {
+        let table = test_table();
+        for seed in 0..32 {
+            let randomized = shuffle_spawns(&table, seed, 3, &[0, 0, 0, 24, 24]);
+            assert!(randomized[..3].iter().all(|id| *id == randomized[0]));
+            assert_eq!(randomized[3], randomized[4]);
+        }
+    }
+
+    #[test]
+    fn fixed_enemies_and_rooms_are_kept() {
+        let table = test_table();
+        for seed in 0..32 {
+            assert_eq!(shuffle_spawns(&table, seed, 3, &[31, 99]), vec![31, 99]);
+            assert_eq!(shuffle_spawns(&table, seed, 7, &[0, 24]), vec![0, 24]);
+        }
+    }
+
+    #[test]
+    fn room_exclusions_only_apply_to_their_room() {
+        let table = test_table();
+        assert!((0..32).all(|seed| shuffle_spawns(&table, seed, 5, &[28]) == vec![28]));
+        assert!((0..32).any(|seed| shuffle_spawns(&table, seed, 3, &[28]) != vec![28]));
+    }
+
+    #[test]
+    fn arachne_is_never_swapped() {
+        for seed in 0..32 {
+            assert_eq!(shuffle_spawns(&ENEMY_TABLE, seed, 3, &[28]), vec![28]);
+            assert!(!shuffle_spawns(&ENEMY_TABLE, seed, 3, &[24, 25]).contains(&28));
+        }
+    }
+
+    #[test]
+    fn reloaded_room_shuffles_the_original() {
+        let table = test_table();
+        let original = vec![0, 24];
+        let randomized = shuffle_spawns(&table, 1, 3, &original);
+        let saved = (original.clone(), randomized.clone());
+        assert_eq!(get_original_spawns(Some(&saved), &randomized), original);
+        assert_eq!(
+            shuffle_spawns(
+                &table,
+                1,
+                3,
+                &get_original_spawns(Some(&saved), &randomized)
+            ),
+            randomized
+        );
+        // A freshly loaded list is taken as is
+        assert_eq!(get_original_spawns(Some(&saved), &[4, 25]), vec![4, 25]);
+        assert_eq!(get_original_spawns(None, &original), original);
+    }
+
+    #[test]
+    fn seed_changes_the_result() {
+        let table = test_table();
+        let spawns = [0, 24];
+        let first = shuffle_spawns(&table, 0, 3, &spawns);
+        assert!((1..32).any(|seed| shuffle_spawns(&table, seed, 3, &spawns) != first));
+    }
+}
@@ -1,16 +1,17 @@
 use crate::constants::{
-    BASE_HP, Difficulty, GUN_NAMES, ITEM_MAP, ITEM_OFFSET_MAP, ItemCategory, MAX_HP, MAX_MAGIC,
-    MELEE_NAMES, ONE_ORB, get_items_by_category, get_weapon_id,
+    BASE_HP, Character, Difficulty, EQUIPPED_STYLE_OFFSET, GUN_NAMES, ITEM_MAP, ITEM_OFFSET_MAP,
+    ItemCategory, MAX_HP, MAX_MAGIC, MELEE_NAMES, ONE_ORB, STYLE_LEVEL_OFFSET, VERGIL_BASE_MAGIC,
+    VERGIL_MELEE_NAMES, add_capped_consumable, get_item_by_id, get_items_by_category,
+    get_weapon_id,
 };
 use crate::hook::ORIGINAL_GIVE_STYLE_XP;
-use crate::mapping::MAPPING;
+use crate::mapping::{MAPPING, Mapping};
 use crate::utilities;
 use crate::utilities::{DMC3_ADDRESS, get_inv_address, read_data_from_address};
-use archipelago_rs::Item;
 use randomizer_utilities::replace_single_byte;
 use std::collections::HashSet;
 use std::ptr::{read_unaligned, write_unaligned};
-use std::sync::{LazyLock, RwLock};
+use std::sync::{LazyLock, OnceLock, RwLock};
 
 pub(crate) const GAME_SESSION_DATA: usize = 0xC8F250;
 
@@ -20,7 +21,7 @@ pub(crate) struct ArchipelagoData {
     pub(crate) purple_orbs: i32,
     pub(crate) dt_unlocked: bool,
     gun_levels: [u32; 5],
-    style_levels: [i32; 4],
+    style_levels: [i32; 6],
     pub(crate) stinger_level: u8,
     pub(crate) jet_stream_level: u8,
     pub(crate) reverb_level: u8,
@@ -28,6 +29,14 @@ pub(crate) struct ArchipelagoData {
     pub(crate) beowulf_level: u8,
     pub(crate) items: HashSet<String>,
     pub(crate) skills: HashSet<usize>,
+    /// Difficulties unlocked by their specific item
+    unlocked_difficulties: Vec<Difficulty>,
+    progressive_difficulty: u8,
+    mission_keys: [bool; 20],
+    /// Costume unlock bits received as items, same layout as the game's costume flags
+    unlocked_costumes: u8,
+    /// Whether the starter items and per character defaults have been applied since the last reset
+    pub(crate) starting_state_applied: bool,
 }
 
 #[derive(Copy, Clone, strum_macros::Display, strum_macros::FromRepr)]
@@ -36,6 +45,8 @@ pub(crate) enum Style {
     Swordmaster = 1,
     Gunslinger = 2,
     Royalguard = 3,
+    Quicksilver = 4,
+    Doppelganger = 5,
 }
 
 impl Style {
@@ -49,6 +60,17 @@ impl Style {
             Style::Swordmaster => 0,
             Style::Gunslinger => 1,
             Style::Royalguard => 3,
+            Style::Quicksilver => 4,
+            Style::Doppelganger => 5,
+        }
+    }
+
+    /// The item that unlocks the style, only relevant for the two extra styles
+    pub fn get_item_name(&self) -> Option<&'static str> {
+        match &self {
+            Style::Quicksilver => Some("Quicksilver Style"),
+            Style::Doppelganger => Some("Doppelganger Style"),
+            _ => None,
         }
     }
 }
@@ -105,26 +127,94 @@ impl ArchipelagoData {
         self.style_levels[style.index()] = (self.style_levels[style.index()] + 1).min(3);
     }
 
-    pub(crate) fn get_style_unlocked(&self) -> [bool; 4] {
-        let mut style_table = [false; 4];
+    pub(crate) fn get_gun_level(&self, gun_index: usize) -> u32 {
+        self.gun_levels.get(gun_index).copied().unwrap_or_default()
+    }
+
+    pub(crate) fn get_style_level(&self, style: Style) -> i32 {
+        self.style_levels[style.index()]
+    }
+
+    pub(crate) fn get_style_unlocked(&self) -> [bool; 6] {
+        let mut style_table = [false; 6];
         for (out, level) in style_table.iter_mut().zip(self.style_levels.iter()) {
             *out = *level > 0;
         }
         style_table
     }
 
-    // Sword, Gun, Trick, Royal for style_levels
-    // Trick, Sword, Gun, Royal - Default order
+    // Sword, Gun, Trick, Royal, Quicksilver, Doppelganger for style_levels
+    // Trick, Sword, Gun, Royal, Quicksilver, Doppelganger - Default order
     fn get_style_level_array(&self) -> [u32; 6] {
         let mut style_levels = [0, 0, 0, 0, 0, 0];
         style_levels[0] = (self.style_levels[1] - 1).max(0) as u32;
         style_levels[1] = (self.style_levels[2] - 1).max(0) as u32;
         style_levels[2] = (self.style_levels[0] - 1).max(0) as u32;
         style_levels[3] = (self.style_levels[3] - 1).max(0) as u32;
+        style_levels[4] = (self.style_levels[4] - 1).max(0) as u32;
+        style_levels[5] = (self.style_levels[5] - 1).max(0) as u32;
 
         style_levels
     }
 
+    pub(crate) fn add_difficulty(&mut self, difficulty: Difficulty) {
+        if !self.unlocked_difficulties.contains(&difficulty) {
+            self.unlocked_difficulties.push(difficulty);
+        }
+    }
+
+    pub(crate) fn add_progressive_difficulty(&mut self) {
+        self.progressive_difficulty = (self.progressive_difficulty + 1).min(6);
+    }
+
+    /// Initial difficulties, plus the ones from items. Progressive ones unlock in order of difficulty
+    pub(crate) fn get_unlocked_difficulties(&self, initial: &[Difficulty]) -> Vec<Difficulty> {
+        let mut unlocked = vec![Difficulty::Normal];
+        for difficulty in initial.iter().chain(self.unlocked_difficulties.iter()) {
+            if !unlocked.contains(difficulty) {
+                unlocked.push(*difficulty);
+            }
+        }
+        let mut remaining = self.progressive_difficulty;
+        for difficulty in (0..6).filter_map(Difficulty::from_repr) {
+            if remaining == 0 {
+                break;
+            }
+            if !unlocked.contains(&difficulty) {
+                unlocked.push(difficulty);
+                remaining -= 1;
+            }
+        }
+        unlocked
+    }
+
+    pub(crate) fn add_mission_key(&mut self, mission: u32) {
+        if (1..=20).contains(&mission) {
+            self.mission_keys[(mission - 1) as usize] = true;
+        }
+    }
+
+    /// Missions that have their key, in mission order. Mission 1 is the fallback so there's always something to play
+    pub(crate) fn get_unlocked_missions(&self) -> Vec<u32> {
+        let unlocked: Vec<u32> = (1..=20)
+            .filter(|mission| self.mission_keys[(mission - 1) as usize])
+            .collect();
+        if unlocked.is_empty() {
+            return vec![1];
+        }
+        unlocked
+    }
+
+    pub(crate) fn add_costume(&mut self, bit: u32) {
+        if bit < u8::BITS {
+            self.unlocked_costumes |= 1 << bit;
+        }
+    }
+
+    pub(crate) fn get_unlocked_costumes(&self) -> u8 {
+        self.unlocked_costumes
+    }
+
     pub(crate) fn add_stinger_level(&mut self) {
         self.stinger_level = (self.stinger_level + 1).min(2);
     }
@@ -166,7 +256,7 @@ pub struct SessionData {
     pub(crate) unknown8: [u8; 20],
     pub melee_index: u32,
     pub gun_index: u32,
-    costume: u8,
+    pub(crate) costume: u8,
     pub unlocked_dt: bool,
     pub unknown9: [u8; 2],
     pub max_hp: f32,
@@ -223,11 +313,40 @@ pub fn get_mission() -> u32 {
     })
 }
 
+/// Whether the current save is a Vergil one
+pub(crate) fn playing_vergil() -> bool {
+    with_session_read(|s| s.char == Character::Vergil as u8).unwrap_or(false)
+}
+
 /// Get current room
 pub fn get_room() -> i32 {
     with_session_read(|s| s.room).unwrap()
 }
 
+/// The difficulty missions need to be cleared on, if difficulties are items it can't be more than what's unlocked
+pub(crate) fn get_required_clear_difficulty(mapping: &Mapping) -> Difficulty {
+    if !mapping.progressive_difficulty {
+        return mapping.mission_clear_difficulty;
+    }
+    match ARCHIPELAGO_DATA.read() {
+        Ok(data) => data
+            .get_unlocked_difficulties(&mapping.initially_unlocked_difficulties)
+            .into_iter()
+            .reduce(|a, b| if b > a { b } else { a })
+            .map_or(mapping.mission_clear_difficulty, |highest| {
+                if highest < mapping.mission_clear_difficulty {
+                    highest
+                } else {
+                    mapping.mission_clear_difficulty
+                }
+            }),
+        Err(err) => {
+            log::error!("Failed to read data from ARCHIPELAGO_DATA: {}", err);
+            mapping.mission_clear_difficulty
+        }
+    }
+}
+
 /// Get current difficulty
 pub fn get_difficulty() -> Difficulty {
     Difficulty::from_repr(
@@ -399,7 +518,13 @@ pub fn set_max_hp_and_magic() {
                 s.max_hp = f32::min(BASE_HP + (data.blue_orbs as f32 * ONE_ORB), MAX_HP);
                 log::debug!("New HP is: {}", s.max_hp);
                 if data.dt_unlocked {
-                    s.max_magic = f32::min(data.purple_orbs as f32 * ONE_ORB, MAX_MAGIC);
+                    let base_magic = if s.char == Character::Vergil as u8 {
+                        VERGIL_BASE_MAGIC
+                    } else {
+                        0.0
+                    };
+                    s.max_magic =
+                        f32::min(base_magic + data.purple_orbs as f32 * ONE_ORB, MAX_MAGIC);
                 } else {
                     s.max_magic = 0.0
                 }
@@ -446,35 +571,58 @@ pub(crate) fn kill_dante() {
     }
 }
 
+/// Put a weapon into the empty second slot for its kind, if it isn't already the first
+fn slot_in_weapon(s: &mut SessionData, weapon: &str) {
+    let weapon_id = get_weapon_id(weapon);
+    let (first, second, kind) = if GUN_NAMES.contains(&weapon) {
+        (2, 3, "gun")
+    } else {
+        (0, 1, "melee")
+    };
+    if s.weapons[first] != weapon_id && s.weapons[second] == 0xFF {
+        log::debug!("Inserting {} into second {} slot", weapon, kind);
+        s.weapons[second] = weapon_id;
+    }
+}
+
 pub fn set_session_weapons() {
     if let Ok(data) = ARCHIPELAGO_DATA.read() {
         with_session(|s| {
-            for weapon in get_items_by_category(ItemCategory::Weapon) {
-                if data.items.contains(weapon) {
-                    let weapon_id = get_weapon_id(weapon);
-                    if MELEE_NAMES.contains(&weapon)
-                        && s.weapons[0] != weapon_id
-                        && s.weapons[1] == 0xFF
-                    {
-                        log::debug!("Inserting {} into second melee slot", weapon);
-                        s.weapons[1] = weapon_id;
-                    }
-
-                    if GUN_NAMES.contains(&weapon)
-                        && s.weapons[2] != weapon_id
-                        && s.weapons[3] == 0xFF
-                    {
-                        log::debug!("Inserting {} into second gun slot", weapon);
-                        s.weapons[3] = weapon_id;
+            if s.char == Character::Vergil as u8 {
+                for weapon in VERGIL_MELEE_NAMES.iter() {
+                    if data.items.contains(*weapon) {
+                        slot_in_weapon(s, weapon);
                     }
                 }
+                return;
+            }
+            for weapon in get_items_by_category(ItemCategory::Weapon) {
+                if data.items.contains(weapon)
+                    && (MELEE_NAMES.contains(&weapon) || GUN_NAMES.contains(&weapon))
+                {
+                    slot_in_weapon(s, weapon);
+                }
             }
         })
         .unwrap();
     }
 }
+
+/// Slot in one of Vergil's weapons as soon as it's received
+pub(crate) fn add_vergil_weapon(weapon: &str) {
+    if !playing_vergil() {
+        return;
+    }
+    if with_session(|s| slot_in_weapon(s, weapon)).is_err() {
+        log::warn!("Failed to slot in {}", weapon);
+    }
+}
 //const WEAPON_SLOT: usize = 0x045FF2D8;
 pub(crate) fn set_weapons_in_inv() {
+    if playing_vergil() {
+        // Vergil's weapons aren't inventory items
+        return;
+    }
     let mut flag;
     if let Ok(data) = ARCHIPELAGO_DATA.read() {
         for weapon in get_items_by_category(ItemCategory::Weapon) {
@@ -537,15 +685,60 @@ pub(crate) fn set_style_levels() {
     .unwrap();
 }
 
+/// The style to switch to if the equipped one (in internal order) hasn't been received yet
+fn get_replacement_style(unlocked: &[bool; 6], equipped: u32) -> Option<Style> {
+    let equipped = (0..unlocked.len())
+        .filter_map(Style::from_repr)
+        .find(|style| style.get_internal_order() == equipped as usize)?;
+    if unlocked[equipped.index()] {
+        return None;
+    }
+    let style = unlocked
+        .iter()
+        .position(|&x| x)
+        .and_then(Style::from_repr)?;
+    log::debug!("{} is not unlocked, switching to {}", equipped, style);
+    Some(style)
+}
+
+/// Swap off of the equipped style if it hasn't been received yet, for both the session and the active character
+pub(crate) fn validate_equipped_style() {
+    let unlocked = match ARCHIPELAGO_DATA.read() {
+        Ok(data) => data.get_style_unlocked(),
+        Err(err) => {
+            log::error!("Failed to read data from ARCHIPELAGO_DATA: {}", err);
+            return;
+        }
+    };
+    with_session(|s| {
+        if let Some(style) = get_replacement_style(&unlocked, s.style) {
+            s.style = style.get_internal_order() as u32;
+        }
+    })
+    .unwrap();
+    if let Some(char_data_ptr) = utilities::get_active_char_address() {
+        let equipped = read_data_from_address::<u32>(char_data_ptr + EQUIPPED_STYLE_OFFSET);
+        if let Some(style) = get_replacement_style(&unlocked, equipped) {
+            unsafe {
+                write_unaligned(
+                    (char_data_ptr + EQUIPPED_STYLE_OFFSET) as *mut u32,
+                    style.get_internal_order() as u32,
+                )
+            }
+        }
+    }
+}
+
 pub(crate) fn apply_style_levels(style: Style) {
     //set_style_levels();
     if let Some(char_data_ptr) = utilities::get_active_char_address() {
         unsafe {
             const LEVEL_1_XP: f32 = 30000f32; // XP To get to LV2
             const LEVEL_2_XP: f32 = 99999f32; // LV2 -> LV3
-            let equipped_style = read_data_from_address::<u32>(char_data_ptr + 0x6338) as usize;
+            let equipped_style =
+                read_data_from_address::<u32>(char_data_ptr + EQUIPPED_STYLE_OFFSET) as usize;
             if style.get_internal_order() == equipped_style {
-                let level = read_data_from_address::<u32>(char_data_ptr + 0x6358);
+                let level = read_data_from_address::<u32>(char_data_ptr + STYLE_LEVEL_OFFSET);
                 if let Some(char_data_ptr) = utilities::get_active_char_address() {
                     match level {
                         0 => {
@@ -591,24 +784,112 @@ where
     }
 }
 
-pub(crate) fn add_consumable(item: Item) {
-    log::debug!("Adding Consumable item {}", item);
+pub(crate) fn add_consumable(id: u32, name: &str) {
+    log::debug!("Adding Consumable item {}", name);
+    let Some(item) = get_item_by_id(id) else {
+        log::error!("Unknown consumable {} ({:#X})", name, id);
+        return;
+    };
+    let refund = with_session(|session| {
+        let (count, refund) = add_capped_consumable(item, session.items[id as usize]);
+        session.items[id as usize] = count;
+        refund
+    })
+    .unwrap();
+    if refund > 0 {
+        log::debug!("{} is at max, refunding {} red orbs", name, refund);
+        give_red_orbs(refund);
+        return;
+    }
     // Add to mission inv
     if let Some(inv_addr) = get_inv_address()
-        && let Some(offset) = ITEM_OFFSET_MAP.get(item.name().as_str())
+        && let Some(offset) = ITEM_OFFSET_MAP.get(name)
     {
         unsafe {
-            replace_single_byte(
-                inv_addr + *offset as usize,
-                read_data_from_address::<u8>(inv_addr + *offset as usize).saturating_add(1),
+            let (count, _) = add_capped_consumable(
+                item,
+                read_data_from_address::<u8>(inv_addr + *offset as usize),
             );
+            replace_single_byte(inv_addr + *offset as usize, count);
         }
     }
+}
 
-    with_session(|session| {
-        session.items[item.id() as usize] += 1;
-    })
-    .unwrap();
+/// Red orbs the player currently has, the mission's count if one is loaded
+pub(crate) fn get_red_orbs() -> i32 {
+    with_mission_data(|m| m.red_orbs)
+        .or_else(|_| with_session_read(|s| s.red_orbs))
+        .unwrap_or_default()
+}
+
+/// Scale a change in red orbs (A pickup) by the given multiplier
+pub(crate) fn scale_red_orb_change(orbs_before: i32, scale: f32) {
+    let change = get_red_orbs() - orbs_before;
+    if change == 0 || scale == 1.0 {
+        return;
+    }
+    let extra = ((change as f32 * scale).round() as i32 - change).max(-(orbs_before + change));
+    log::debug!(
+        "Scaling red orb change of {} by {} ({:+})",
+        change,
+        scale,
+        extra
+    );
+    give_red_orbs(extra);
+}
+
+/// Red orb prices for the item shop, gun levels and skills. The shops read their displayed price,
+/// the affordability check and the amount taken from here.
+/// Not from DDMK, so the table is checked before anything is written to it
+const SHOP_PRICE_TABLE: usize = 0x4F6F20;
+const SHOP_PRICE_COUNT: usize = 64;
+/// The game's own prices, so scaling again on reconnect doesn't stack. None if the table didn't look right
+static ORIGINAL_SHOP_PRICES: OnceLock<Option<[u32; SHOP_PRICE_COUNT]>> = OnceLock::new();
+
+/// Every vanilla price is a whole hundred red orbs that fits in the orb counter, unused slots are 0
+fn is_vanilla_price_table(prices: &[u32]) -> bool {
+    const MAX_PRICE: u32 = 999999;
+    prices.iter().any(|price| *price > 0)
+        && prices
+            .iter()
+            .all(|price| price % 100 == 0 && *price <= MAX_PRICE)
+}
+
+fn write_shop_prices(prices: [u32; SHOP_PRICE_COUNT]) {
+    let table_address = SHOP_PRICE_TABLE + *DMC3_ADDRESS;
+    if let Err(err) = randomizer_utilities::modify_protected_memory(
+        || unsafe { write_unaligned(table_address as *mut [u32; SHOP_PRICE_COUNT], prices) },
+        table_address as *mut [u32; SHOP_PRICE_COUNT],
+    ) {
+        log::error!("Failed to write shop prices: {:?}", err);
+    }
+}
+
+/// Scale every shop price by the given multiplier
+pub(crate) fn scale_shop_prices(scale: f32) {
+    let original = ORIGINAL_SHOP_PRICES.get_or_init(|| {
+        let prices =
+            read_data_from_address::<[u32; SHOP_PRICE_COUNT]>(SHOP_PRICE_TABLE + *DMC3_ADDRESS);
+        if is_vanilla_price_table(&prices) {
+            Some(prices)
+        } else {
+            log::error!(
+                "Shop price table at {:#X} doesn't hold the game's prices, leaving them as is",
+                SHOP_PRICE_TABLE
+            );
+            None
+        }
+    });
+    if let Some(original) = original {
+        write_shop_prices(original.map(|price| (price as f32 * scale.max(0.0)).round() as u32));
+    }
+}
+
+/// Put the game's own prices back
+pub(crate) fn restore_shop_prices() {
+    if let Some(Some(original)) = ORIGINAL_SHOP_PRICES.get() {
+        write_shop_prices(*original);
+    }
 }
 
 pub(crate) fn give_red_orbs(orbs: i32) {
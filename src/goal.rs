@@ -0,0 +1,331 @@
+use crate::constants::Rank;
+use crate::data::generated_locations;
+use crate::game_manager::with_rankings_read;
+use crate::mapping::{Goal, Mapping, ModModeData};
+use archipelago_rs::{Client, ClientStatus, DataStorageOperation};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::error::Error;
+use std::fs;
+use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+use std::sync::{LazyLock, RwLock};
+
+/// Highest Bloody Palace floor reached in the current room
+static BP_FLOOR_REACHED: AtomicU16 = AtomicU16::new(0);
+/// Set when progress changes in a hook, the AP loop then saves it and rechecks the goal
+static PROGRESS_CHANGED: AtomicBool = AtomicBool::new(false);
+/// Number of story missions, taken from the mission complete locations
+static MISSION_COUNT: LazyLock<u32> = LazyLock::new(|| {
+    generated_locations::ITEM_MISSION_MAP
+        .keys()
+        .filter(|name| name.starts_with("Mission #") && name.ends_with(" Complete"))
+        .count() as u32
+});
+
+/// Latest goal progress, read by the overlay
+pub static GOAL_STATUS: LazyLock<RwLock<Option<GoalStatus>>> = LazyLock::new(|| RwLock::new(None));
+
+/// Missions that end with a fight against Vergil
+pub const VERGIL_MISSIONS: [u32; 3] = [7, 13, 20];
+const SECRET_MISSION_COUNT: u32 = 12;
+const NOT_COMPLETED: u8 = 0xFF;
+
+/// A goal that can be used on its own or combined with others
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(tag = "type")]
+pub enum GoalCondition {
+    /// Complete the missions required by `Mapping.goal`
+    Missions,
+    /// Reach the specified Bloody Palace floor
+    BloodyPalaceFloor { floor: u16 },
+    /// Get an SS rank (or better) on each of the listed missions, on any difficulty
+    SSRanks { missions: Vec<u32> },
+    /// Complete all 12 secret missions
+    SecretMissions,
+    /// Defeat Vergil in each of his encounters
+    Vergil,
+    /// Any one of the goals
+    Any { goals: Vec<GoalCondition> },
+    /// Every one of the goals
+    All { goals: Vec<GoalCondition> },
+}
+
+impl std::fmt::Display for GoalCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GoalCondition::Missions => write!(f, "Missions"),
+            GoalCondition::BloodyPalaceFloor { floor } => write!(f, "BP Floor {}", floor),
+            GoalCondition::SSRanks { missions } => write!(f, "SS Rank x{}", missions.len()),
+            GoalCondition::SecretMissions => write!(f, "Secret Missions"),
+            GoalCondition::Vergil => write!(f, "Vergil"),
+            GoalCondition::Any { goals } => write!(f, "Any of {}", goals.len()),
+            GoalCondition::All { goals } => write!(f, "All of {}", goals.len()),
+        }
+    }
+}
+
+/// Snapshot of how far along the goal is, this is also what gets written to DataStorage for trackers
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct GoalStatus {
+    pub goal: String,
+    pub conditions: Option<String>,
+    pub missions_completed: u32,
+    pub missions_required: u32,
+    /// Next mission to clear, following mission_order under RandomOrder
+    pub next_mission: Option<u32>,
+    pub bp_floor: u16,
+    pub goal_reached: bool,
+}
+
+/// Progress that isn't tracked by the server or the save file
+#[derive(Deserialize, Serialize, Debug, Default)]
+struct GoalProgress {
+    bp_floor: u16,
+}
+
+fn get_progress_path(client: &Client<ModModeData>) -> Result<String, Box<dyn Error>> {
+    Ok(format!(
+        "{}{}",
+        randomizer_utilities::get_room_path(client)?,
+        "goal_progress.json"
+    ))
+}
+
+/// Load any locally stored goal progress for the room
+pub(crate) fn load_progress(client: &Client<ModModeData>) {
+    BP_FLOOR_REACHED.store(0, Ordering::SeqCst);
+    PROGRESS_CHANGED.store(false, Ordering::SeqCst);
+    clear_status();
+    let progress = get_progress_path(client)
+        .and_then(|path| Ok(fs::read_to_string(path)?))
+        .and_then(|str| Ok(serde_json::from_str::<GoalProgress>(&str)?));
+    match progress {
+        Ok(progress) => {
+            BP_FLOOR_REACHED.store(progress.bp_floor, Ordering::SeqCst);
+        }
+        Err(err) => {
+            log::debug!("No goal progress loaded: {}", err);
+        }
+    }
+}
+
+fn save_progress(client: &Client<ModModeData>) -> Result<(), Box<dyn Error>> {
+    let progress = GoalProgress {
+        bp_floor: BP_FLOOR_REACHED.load(Ordering::SeqCst),
+    };
+    fs::write(
+        get_progress_path(client)?,
+        serde_json::to_string(&progress)?,
+    )?;
+    Ok(())
+}
+
+pub(crate) fn get_bp_floor_reached() -> u16 {
+    BP_FLOOR_REACHED.load(Ordering::SeqCst)
+}
+
+/// Record a newly reached Bloody Palace floor. Called from a game hook, so saving and checking the
+/// goal is left to `update_progress`
+pub(crate) fn on_bp_floor_reached(floor: u16) {
+    if BP_FLOOR_REACHED.fetch_max(floor, Ordering::SeqCst) < floor {
+        PROGRESS_CHANGED.store(true, Ordering::SeqCst);
+    }
+}
+
+/// Run from the AP loop, saves progress made since the last call and sees if it completed the goal
+pub(crate) fn update_progress(client: &mut Client<ModModeData>) -> Result<(), Box<dyn Error>> {
+    if PROGRESS_CHANGED.swap(false, Ordering::SeqCst) {
+        save_progress(client)?;
+        check_goal(client)?;
+    }
+    Ok(())
+}
+
+/// Send out the goal status if the goal has been reached
+pub(crate) fn check_goal(client: &mut Client<ModModeData>) -> Result<(), Box<dyn Error>> {
+    update_status(client)?;
+    if has_reached_goal(client) {
+        log::info!("Goal reached!");
+        client.set_status(ClientStatus::Goal)?
+    }
+    Ok(())
+}
+
+pub(crate) fn is_goal_reached() -> bool {
+    GOAL_STATUS
+        .read()
+        .is_ok_and(|status| status.as_ref().is_some_and(|status| status.goal_reached))
+}
+
+pub(crate) fn clear_status() {
+    match GOAL_STATUS.write() {
+        Ok(mut status) => *status = None,
+        Err(err) => log::error!("Failed to clear goal status: {}", err),
+    }
+}
+
+/// Recalculate the goal progress, reporting it to DataStorage if it changed
+fn update_status(client: &mut Client<ModModeData>) -> Result<(), Box<dyn Error>> {
+    let ModModeData::Normal(mapping) = client.slot_data() else {
+        return Ok(());
+    };
+    let checked: HashSet<String> = client
+        .checked_locations()
+        .map(|loc| loc.name().to_string())
+        .collect();
+    let order = mission_path(mapping);
+    let status = GoalStatus {
+        goal: mapping.goal.to_string(),
+        conditions: mapping.goal_conditions.as_ref().map(|c| c.to_string()),
+        missions_completed: order
+            .iter()
+            .filter(|m| checked.contains(&mission_complete_name(**m)))
+            .count() as u32,
+        missions_required: order.len() as u32,
+        next_mission: order
+            .iter()
+            .find(|m| !checked.contains(&mission_complete_name(**m)))
+            .copied(),
+        bp_floor: get_bp_floor_reached(),
+        goal_reached: is_condition_met(
+            mapping
+                .goal_conditions
+                .as_ref()
+                .unwrap_or(&GoalCondition::Missions),
+            mapping,
+            &checked,
+        ),
+    };
+    let mut current = GOAL_STATUS.write()?;
+    if current.as_ref() == Some(&status) {
+        return Ok(());
+    }
+    report_status(client, &status)?;
+    current.replace(status);
+    Ok(())
+}
+
+fn report_status(
+    client: &mut Client<ModModeData>,
+    status: &GoalStatus,
+) -> Result<(), Box<dyn Error>> {
+    let key = format!(
+        "dmc3_goal_progress_{}_{}",
+        client.this_player().team(),
+        client.this_player().slot()
+    );
+    let value = serde_json::to_value(status)?;
+    client.set(
+        key,
+        serde_json::Value::Null,
+        false,
+        vec![DataStorageOperation::Replace(value)],
+    )?;
+    Ok(())
+}
+
+/// Missions in the order they are meant to be cleared
+fn mission_path(mapping: &Mapping) -> Vec<u32> {
+    match (&mapping.goal, &mapping.mission_order) {
+        (Goal::RandomOrder, Some(order)) => order.iter().map(|m| *m as u32).collect(),
+        _ => (1..=*MISSION_COUNT).collect(),
+    }
+}
+
+fn has_reached_goal(client: &Client<ModModeData>) -> bool {
+    match client.slot_data() {
+        ModModeData::HintGame(_) => {
+            log::error!("Trying to check for goal in HintGame mode");
+            false
+        }
+        ModModeData::Normal(mapping) => {
+            let checked: HashSet<String> = client
+                .checked_locations()
+                .map(|loc| loc.name().to_string())
+                .collect();
+            is_condition_met(
+                mapping
+                    .goal_conditions
+                    .as_ref()
+                    .unwrap_or(&GoalCondition::Missions),
+                mapping,
+                &checked,
+            )
+        }
+    }
+}
+
+fn is_condition_met(
+    condition: &GoalCondition,
+    mapping: &Mapping,
+    checked: &HashSet<String>,
+) -> bool {
+    match condition {
+        GoalCondition::Missions => missions_complete(mapping, checked),
+        GoalCondition::BloodyPalaceFloor { floor } => get_bp_floor_reached() >= *floor,
+        GoalCondition::SSRanks { missions } => missions
+            .iter()
+            .all(|mission| has_ss_rank(*mission, checked)),
+        GoalCondition::SecretMissions => {
+            (1..=SECRET_MISSION_COUNT).all(|i| checked.contains(&format!("Secret Mission #{}", i)))
+        }
+        GoalCondition::Vergil => VERGIL_MISSIONS
+            .iter()
+            .all(|mission| checked.contains(&mission_complete_name(*mission))),
+        GoalCondition::Any { goals } => goals
+            .iter()
+            .any(|goal| is_condition_met(goal, mapping, checked)),
+        GoalCondition::All { goals } => goals
+            .iter()
+            .all(|goal| is_condition_met(goal, mapping, checked)),
+    }
+}
+
+pub(crate) fn mission_complete_name(mission: u32) -> String {
+    format!("Mission #{} Complete", mission)
+}
+
+fn missions_complete(mapping: &Mapping, checked: &HashSet<String>) -> bool {
+    match mapping.goal {
+        Goal::Standard => checked.contains(&mission_complete_name(20)),
+        // If we are missing a mission complete check then we cannot goal
+        Goal::All | Goal::MissionKeys => {
+            (1..=*MISSION_COUNT).all(|i| checked.contains(&mission_complete_name(i)))
+        }
+        Goal::RandomOrder => {
+            if let Some(last) = mapping
+                .mission_order
+                .as_ref()
+                .and_then(|order| order.last())
+            {
+                return checked.contains(&mission_complete_name(*last as u32));
+            }
+            false
+        }
+    }
+}
+
+/// Either the SS Rank location was checked, or the save has an SS rank on any difficulty
+fn has_ss_rank(mission: u32, checked: &HashSet<String>) -> bool {
+    if checked.contains(&format!("Mission #{} SS Rank", mission)) {
+        return true;
+    }
+    if !(1..=*MISSION_COUNT).contains(&mission) {
+        log::error!("Invalid mission for SS rank goal: {}", mission);
+        return false;
+    }
+    let idx = (mission - 1) as usize;
+    with_rankings_read(|r| {
+        [
+            r.easy_ranking,
+            r.normal_ranking,
+            r.hard_ranking,
+            r.very_hard_ranking,
+            r.dmd_ranking,
+            r.hoh_ranking,
+        ]
+        .iter()
+        .any(|rankings| rankings[idx] != NOT_COMPLETED && rankings[idx] >= Rank::SS as u8)
+    })
+    .unwrap_or(false)
+}
@@ -1,7 +1,8 @@
 use crate::DMC3_ADDRESS;
 use crate::MinHook;
 use crate::game_manager::get_mission;
-use crate::{AP_CORE, create_hook};
+use crate::mapping::ModModeData;
+use crate::{AP_CORE, check_handler, create_hook, goal};
 use archipelago_rs::{AsLocationId, Location};
 use minhook::MH_STATUS;
 use oneshot::Receiver;
@@ -102,6 +103,12 @@ fn on_bp_floor_change(param_1: usize, event_data_ptr: usize) -> i32 {
                     .connection
                     .client_mut()
             {
+                if let ModModeData::Normal(mapping) = client.slot_data() {
+                    // Randomizer mode only cares about the floor for checks and goals
+                    check_handler::send_bp_floor_checks(mapping, bp_data.last_level, bp_data.level);
+                    goal::on_bp_floor_reached(bp_data.level);
+                    return res;
+                }
                 let floors_per_hint: u16 = FLOORS_PER_HINT.load(Ordering::SeqCst);
                 let key = format!(
                     "_read_hints_{}_{}",
@@ -4,22 +4,24 @@ use crate::constants::ItemEntry;
 use crate::constants::*;
 use crate::data::generated_locations;
 use crate::game_manager::{
-    ARCHIPELAGO_DATA, Style, get_difficulty, get_mission, get_room, set_item, set_loc_chk_flg,
-    set_weapons_in_inv, with_rankings_read, with_session,
+    ARCHIPELAGO_DATA, ArchipelagoData, Style, get_difficulty, get_mission, get_room, set_item,
+    set_loc_chk_flg, set_weapons_in_inv, with_rankings_read, with_session, with_session_read,
 };
 use crate::location_handler::in_key_item_room;
-use crate::mapping::{Goal, MAPPING, Mapping, run_scouts_for_mission};
-use crate::ui::overlay::CANT_PURCHASE;
+use crate::mapping::{Goal, MAPPING, Mapping, ModModeData, run_scouts_for_mission};
+use crate::ui::checklist;
+use crate::ui::overlay::{ADJUDICATOR_PREVIEW, CANT_PURCHASE, MISSION_SELECT_PREVIEW};
 use crate::ui::text_handler;
 use crate::ui::text_handler::LAST_OBTAINED_ID;
 use crate::utilities::{DMC3_ADDRESS, read_data_from_address};
 use crate::{
-    AP_CORE, archipelago, check_handler, create_hook, game_manager, save_handler, skill_manager,
-    utilities,
+    AP_CORE, archipelago, auto_hint, check_handler, create_hook, door_randomizer, game_manager,
+    goal, save_handler, skill_manager, utilities,
 };
-use archipelago_rs::CreateAsHint;
+use archipelago_rs::{Client, CreateAsHint};
 use bitflags::bitflags;
 use minhook::{MH_STATUS, MinHook};
+use rand::seq::IteratorRandom;
 use randomizer_utilities::archipelago_utilities::DeathLinkData;
 use randomizer_utilities::item_sync::CURRENT_INDEX;
 use randomizer_utilities::replace_single_byte;
@@ -27,7 +29,8 @@ use std::arch::asm;
 use std::cmp::min;
 use std::ptr::{read_unaligned, write};
 use std::sync::atomic::Ordering;
-use std::sync::{LazyLock, OnceLock};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Instant;
 use std::{ptr, slice};
 // 23d680 - Pause menu event? Hook in here to do rendering
 pub(crate) unsafe fn create_hooks() -> Result<(), MH_STATUS> {
@@ -77,7 +80,7 @@ pub(crate) unsafe fn create_hooks() -> Result<(), MH_STATUS> {
         );
         create_hook!(
             SKILL_SHOP_ADDR,
-            deny_skill_purchasing,
+            skill_shop,
             ORIGINAL_SKILL_SHOP,
             "Deny purchases of skills"
         );
@@ -203,73 +206,68 @@ pub static ORIGINAL_EDIT_EVENT: OnceLock<
     unsafe extern "C" fn(param_1: usize, param_2: i32, param_3: usize),
 > = OnceLock::new();
 pub fn edit_event_drop(param_1: usize, param_2: i32, param_3: usize) {
-    let (mapping, mission_event_tables, checked_locations) =
-        if let (Ok(mapping), Some(mission_event_tables), Ok(client)) = (
-            MAPPING.read(),
-            EVENT_TABLES.get(&get_mission()),
-            AP_CORE.get().unwrap().as_ref().lock(),
-        ) && mapping.is_some()
-        {
-            (mapping, mission_event_tables, client)
-        } else {
-            unsafe {
-                if let Some(original) = ORIGINAL_EDIT_EVENT.get() {
-                    original(param_1, param_2, param_3);
-                }
-            }
-            return;
-        };
-    match mapping.as_ref() {
-        None => log::debug!("How did we get here?"),
-        Some(_mapping) => {
-            unsafe {
-                // For each table
-                for event_table in mission_event_tables {
-                    for event in &event_table.events {
-                        if let Some(event_table_addr) = utilities::get_event_address() {
-                            if checked_locations
-                                .connection
-                                .client()
-                                .unwrap()
-                                .checked_locations()
-                                .any(|loc| loc.name() == event_table.location)
-                            {
-                                log::debug!("Event loc checked: {}", &event_table.location);
-                                match event.event_type {
-                                    // If the location has already been checked use DUMMY_ID as a dummy item.
-                                    EventCode::Give => replace_single_byte(
-                                        event_table_addr + event.offset,
-                                        *DUMMY_ID as u8,
-                                    ),
-                                    EventCode::Check => replace_single_byte(
-                                        event_table_addr + event.offset,
-                                        *DUMMY_ID as u8,
-                                    ),
-                                    EventCode::End => replace_single_byte(
-                                        event_table_addr + event.offset,
-                                        *DUMMY_ID as u8,
-                                    ),
-                                }
-                            } else {
-                                log::debug!("Event loc not checked: {}", &event_table.location);
-                                match event.event_type {
-                                    // Location has not been checked off!
-                                    EventCode::Give => {}
-                                    EventCode::Check => {
-                                        log::debug!("Replaced check at {:#X}", &event.offset);
-                                        replace_single_byte(
-                                            event_table_addr + event.offset,
-                                            *DUMMY_ID as u8,
-                                        )
-                                    }
-                                    EventCode::End => {
-                                        log::debug!("Replaced end at {:#X}", &event.offset);
-                                        replace_single_byte(
-                                            event_table_addr + event.offset,
-                                            *DUMMY_ID as u8,
-                                        )
-                                    }
-                                }
+    if let Ok(mapping) = MAPPING.read()
+        && mapping.is_some()
+        && let Ok(core) = AP_CORE.get().unwrap().as_ref().lock()
+        && let Some(client) = core.connection.client()
+    {
+        patch_event_tables(client);
+    }
+    unsafe {
+        if let Some(original) = ORIGINAL_EDIT_EVENT.get() {
+            original(param_1, param_2, param_3);
+        }
+    }
+}
+
+/// Replace the items given/checked by the current mission's event tables depending on if their location is checked
+pub(crate) fn patch_event_tables(client: &Client<ModModeData>) {
+    let Some(mission_event_tables) = EVENT_TABLES.get(&get_mission()) else {
+        return;
+    };
+    unsafe {
+        // For each table
+        for event_table in mission_event_tables {
+            for event in &event_table.events {
+                if let Some(event_table_addr) = utilities::get_event_address() {
+                    if client
+                        .checked_locations()
+                        .any(|loc| loc.name() == event_table.location)
+                    {
+                        log::debug!("Event loc checked: {}", &event_table.location);
+                        match event.event_type {
+                            // If the location has already been checked use DUMMY_ID as a dummy item.
+                            EventCode::Give => replace_single_byte(
+                                event_table_addr + event.offset,
+                                *DUMMY_ID as u8,
+                            ),
+                            EventCode::Check => replace_single_byte(
+                                event_table_addr + event.offset,
+                                *DUMMY_ID as u8,
+                            ),
+                            EventCode::End => replace_single_byte(
+                                event_table_addr + event.offset,
+                                *DUMMY_ID as u8,
+                            ),
+                        }
+                    } else {
+                        log::debug!("Event loc not checked: {}", &event_table.location);
+                        match event.event_type {
+                            // Location has not been checked off!
+                            EventCode::Give => {}
+                            EventCode::Check => {
+                                log::debug!("Replaced check at {:#X}", &event.offset);
+                                replace_single_byte(
+                                    event_table_addr + event.offset,
+                                    *DUMMY_ID as u8,
+                                )
+                            }
+                            EventCode::End => {
+                                log::debug!("Replaced end at {:#X}", &event.offset);
+                                replace_single_byte(
+                                    event_table_addr + event.offset,
+                                    *DUMMY_ID as u8,
+                                )
                             }
                         }
                     }
@@ -277,11 +275,6 @@ pub fn edit_event_drop(param_1: usize, param_2: i32, param_3: usize) {
             }
         }
     }
-    unsafe {
-        if let Some(original) = ORIGINAL_EDIT_EVENT.get() {
-            original(param_1, param_2, param_3);
-        }
-    }
 }
 
 /// Modify the game's code so the "pickup mode" table is correct
@@ -343,12 +336,22 @@ fn modify_adjudicator(
                 && let Some(data) = adjudicator_map.get(*location_name)
             {
                 //log::debug!("New adjudicator data will be {:?}", data);
+                let ranking = data.get_ranking(get_difficulty());
+                let weapon = get_adjudicator_weapon(
+                    mappings,
+                    &ARCHIPELAGO_DATA.read().unwrap(),
+                    &data.weapon,
+                );
                 unsafe {
-                    replace_single_byte(adjudicator_data + RANKING_OFFSET, data.ranking);
-                    replace_single_byte(
-                        adjudicator_data + WEAPON_OFFSET,
-                        get_weapon_id(&data.weapon),
-                    );
+                    replace_single_byte(adjudicator_data + RANKING_OFFSET, ranking);
+                    replace_single_byte(adjudicator_data + WEAPON_OFFSET, get_weapon_id(weapon));
+                }
+                if let Ok(mut preview) = ADJUDICATOR_PREVIEW.write() {
+                    preview.replace(format!(
+                        "Adjudicator: {} Rank with {}",
+                        Rank::from_repr(ranking.saturating_sub(1) as usize).unwrap_or_default(),
+                        weapon
+                    ));
                 }
             }
 
@@ -375,9 +378,49 @@ fn modify_adjudicator(
     }
 }
 
+/// The melee weapon an adjudicator should ask for. If the requested one is unknown or hasn't been obtained,
+/// fall back to one the player owns so the location can still be done
+fn get_adjudicator_weapon<'a>(
+    mapping: &Mapping,
+    data: &ArchipelagoData,
+    weapon: &'a str,
+) -> &'a str {
+    let weapon_names = if mapping.character == Character::Vergil {
+        &VERGIL_MELEE_NAMES
+    } else {
+        &MELEE_NAMES
+    };
+    let owned = |name: &str| {
+        data.items.contains(name)
+            || [mapping.start_melee, mapping.start_second_melee].contains(&get_weapon_id(name))
+    };
+    if weapon_names.iter().any(|name| *name == weapon) && owned(weapon) {
+        return weapon;
+    }
+    match weapon_names.iter().copied().find(|name| owned(name)) {
+        Some(fallback) => {
+            log::warn!(
+                "Adjudicator weapon {} can't be used, falling back to {}",
+                weapon,
+                fallback
+            );
+            fallback
+        }
+        None => {
+            log::error!(
+                "No owned melee weapon to give to adjudicator, using {}",
+                weapon
+            );
+            weapon
+        }
+    }
+}
+
 pub const ITEM_SPAWNS_ADDR: usize = 0x1b4440; // 0x1b4480
 pub static ORIGINAL_ITEM_SPAWNS: OnceLock<unsafe extern "C" fn(loc_chk_id: usize)> =
     OnceLock::new();
+/// The last item spawn table that was loaded and the mission and room it belongs to, used to refresh dummy items
+static LAST_ITEM_SPAWNS: Mutex<Option<(usize, u32, i32)>> = Mutex::new(None);
 fn item_spawns_hook(unknown: usize) {
     unsafe {
         #[allow(unused_assignments)]
@@ -409,23 +452,13 @@ fn item_spawns_hook(unknown: usize) {
                 Some(mapping) => {
                     //modify_adjudicator_drop(mapping);
                     // modify_secret_mission_item(mapping);
-                    for _i in 0..item_count {
-                        let item_ref: &u32 = &*(item_addr as *const u32);
-                        const EXTRA_OUTPUT: bool = false;
-                        if EXTRA_OUTPUT {
-                            log::debug!("Item ID: {} ({:#X})", get_item_name(*item_ref), *item_ref);
-                        }
-                        for (location_name, entry) in generated_locations::ITEM_MISSION_MAP.iter() {
-                            check_and_replace_item(
-                                location_name,
-                                entry,
-                                room_num,
-                                mapping,
-                                item_ref,
-                                item_addr,
-                            );
-                        }
-                        item_addr = item_addr.byte_offset(0x14);
+                    if let Ok(mut last) = LAST_ITEM_SPAWNS.lock() {
+                        last.replace((unknown, get_mission(), room_num));
+                    }
+                    if let Ok(core) = AP_CORE.get().unwrap().lock()
+                        && let Some(client) = core.connection.client()
+                    {
+                        replace_item_spawns(client, mapping, item_addr, item_count, room_num);
                     }
                 }
             },
@@ -439,6 +472,59 @@ fn item_spawns_hook(unknown: usize) {
     }
 }
 
+unsafe fn replace_item_spawns(
+    client: &Client<ModModeData>,
+    mapping: &Mapping,
+    mut item_addr: *mut i32,
+    item_count: u32,
+    room_num: i32,
+) {
+    unsafe {
+        for _i in 0..item_count {
+            let item_ref: &u32 = &*(item_addr as *const u32);
+            const EXTRA_OUTPUT: bool = false;
+            if EXTRA_OUTPUT {
+                log::debug!("Item ID: {} ({:#X})", get_item_name(*item_ref), *item_ref);
+            }
+            for (location_name, entry) in generated_locations::ITEM_MISSION_MAP.iter() {
+                check_and_replace_item(
+                    client,
+                    location_name,
+                    entry,
+                    room_num,
+                    mapping,
+                    item_ref,
+                    item_addr,
+                );
+            }
+            item_addr = item_addr.byte_offset(0x14);
+        }
+    }
+}
+
+/// Re-run the dummy item replacement on the last loaded spawn table, if we are still in its mission and room
+pub(crate) fn refresh_item_spawns(client: &Client<ModModeData>) {
+    let Ok(last) = LAST_ITEM_SPAWNS.lock() else {
+        return;
+    };
+    if let Some((table, mission, room_num)) = *last
+        && mission == get_mission()
+        && room_num == get_room()
+        && let Some(mapping) = MAPPING.read().unwrap().as_ref()
+    {
+        let item_count = read_data_from_address::<u32>(table + 0x06);
+        unsafe {
+            replace_item_spawns(
+                client,
+                mapping,
+                (table + 0x10) as *mut i32,
+                item_count,
+                room_num,
+            );
+        }
+    }
+}
+
 pub const DAMAGE_CALC_ADDR: usize = 0x088190;
 pub static ORIGINAL_DAMAGE_CALC: OnceLock<
     unsafe extern "C" fn(damage_calc: usize, param_1: usize, param_2: usize, param_3: usize),
@@ -526,6 +612,7 @@ fn edit_initial_index(custom_weapon: usize) -> i32 {
 }
 
 unsafe fn check_and_replace_item(
+    client: &Client<ModModeData>,
     location_name: &&str,
     entry: &ItemEntry,
     room_num: i32,
@@ -541,7 +628,7 @@ unsafe fn check_and_replace_item(
     //log::debug!("Room number X: {} Room number memory: {}, Item ID X: {:#X}, Item ID Memory: {:#X}", entry.room_number, room_num, entry.item_id, *item_ref);
     if entry.room_number == room_num && entry.item_id == *item_ref && !entry.adjudicator {
         log::debug!("Seeing if item needs to be dummy");
-        if !dummy_replace(location_name, item_addr) {
+        if !dummy_replace(client, location_name, item_addr) {
             // log::info!(
             //     "Replaced item in room {} ({}) with {} {:#X}",
             //     entry.room_number,
@@ -555,7 +642,7 @@ unsafe fn check_and_replace_item(
 }
 
 /// Replaces an item with a dummy one in order to not immediately proc end events upon entering the location's room
-fn dummy_replace(location_key: &&str, item_addr: *mut i32) -> bool {
+fn dummy_replace(client: &Client<ModModeData>, location_key: &&str, item_addr: *mut i32) -> bool {
     // Get event tables for mission and then each END event
     if let Some(event_tables) = EVENT_TABLES.get(&get_mission()) {
         for event_table in event_tables
@@ -567,21 +654,16 @@ fn dummy_replace(location_key: &&str, item_addr: *mut i32) -> bool {
                 .iter()
                 .filter(|event| event.event_type == EventCode::End)
             {
-                if let Ok(core) = AP_CORE.get().unwrap().lock().as_ref() {
-                    // Then if location in question is checked, replace the item with a dummy and return true
-                    if core
-                        .connection
-                        .client()
-                        .unwrap()
-                        .checked_locations()
-                        .any(|loc| loc.name() == *location_key)
-                    {
-                        unsafe {
-                            *item_addr = *DUMMY_ID as i32;
-                        }
-                        log::info!("Replaced item at {} with dummy item", location_key);
-                        return true;
+                // Then if location in question is checked, replace the item with a dummy and return true
+                if client
+                    .checked_locations()
+                    .any(|loc| loc.name() == *location_key)
+                {
+                    unsafe {
+                        *item_addr = *DUMMY_ID as i32;
                     }
+                    log::info!("Replaced item at {} with dummy item", location_key);
+                    return true;
                 }
             }
         }
@@ -590,6 +672,15 @@ fn dummy_replace(location_key: &&str, item_addr: *mut i32) -> bool {
 }
 
 fn set_relevant_key_items() {
+    if let Ok(core) = AP_CORE.get().unwrap().lock().as_ref()
+        && let Some(client) = core.connection.client()
+    {
+        apply_relevant_key_items(client);
+    }
+}
+
+/// Set the flags for the current mission's key items, based on what has been received and checked
+pub(crate) fn apply_relevant_key_items(client: &Client<ModModeData>) {
     if let Ok(data) = ARCHIPELAGO_DATA.read() {
         with_session(|s| {
             match MISSION_ITEM_MAP.get(&(s.mission)) {
@@ -614,39 +705,37 @@ fn set_relevant_key_items() {
                 }
             }
 
-            if let Ok(core) = AP_CORE.get().unwrap().lock().as_ref() {
-                let mut checked_locations = core.connection.client().unwrap().checked_locations();
-                // Special case for Ignis Fatuus
-                // Needed so the Ignis Fatuus location can be reached even when the actual key item is acquired
-                if get_room() == 302
-                    && let Some(event_table_addr) = utilities::get_event_address()
-                {
-                    if checked_locations.any(|loc| loc.name() == "Mission #8 - Ignis Fatuus") {
-                        // If we have the location checked, continue normal routing
-                        unsafe {
-                            write((event_table_addr + 0x748) as _, 311);
-                        }
-                    } else {
-                        // If location not checked, alter event to get to it
-                        unsafe {
-                            write((event_table_addr + 0x748) as _, 303);
-                        }
+            let mut checked_locations = client.checked_locations();
+            // Special case for Ignis Fatuus
+            // Needed so the Ignis Fatuus location can be reached even when the actual key item is acquired
+            if get_room() == 302
+                && let Some(event_table_addr) = utilities::get_event_address()
+            {
+                if checked_locations.any(|loc| loc.name() == "Mission #8 - Ignis Fatuus") {
+                    // If we have the location checked, continue normal routing
+                    unsafe {
+                        write((event_table_addr + 0x748) as _, 311);
+                    }
+                } else {
+                    // If location not checked, alter event to get to it
+                    unsafe {
+                        write((event_table_addr + 0x748) as _, 303);
                     }
                 }
+            }
 
-                if let Ok(loc) = in_key_item_room() {
-                    log::debug!("In key room: {}", loc);
-                    if !checked_locations.any(|location| location.name() == loc) {
-                        set_loc_chk_flg(
-                            get_item_name(
-                                generated_locations::ITEM_MISSION_MAP
-                                    .get(loc)
-                                    .unwrap()
-                                    .item_id,
-                            ),
-                            false,
-                        );
-                    }
+            if let Ok(loc) = in_key_item_room() {
+                log::debug!("In key room: {}", loc);
+                if !checked_locations.any(|location| location.name() == loc) {
+                    set_loc_chk_flg(
+                        get_item_name(
+                            generated_locations::ITEM_MISSION_MAP
+                                .get(loc)
+                                .unwrap()
+                                .item_id,
+                        ),
+                        false,
+                    );
                 }
             }
         })
@@ -668,12 +757,23 @@ pub fn load_new_room(param_1: usize) -> bool {
     }
     set_weapons_in_inv();
     set_relevant_key_items();
+    auto_hint::reset_shop_visit();
+    clear_skill_shop_checks();
     check_handler::clear_high_roller();
+    if let Ok(mut preview) = ADJUDICATOR_PREVIEW.write() {
+        *preview = None;
+    }
     LAST_OBTAINED_ID.store(0, Ordering::SeqCst); // Should stop random item jumpscares
-    if let Some(mapping) = MAPPING.read().unwrap().as_ref()
-        && mapping.randomize_skills
-    {
-        skill_manager::set_skills(&ARCHIPELAGO_DATA.read().unwrap());
+    text_handler::set_display_graphic(None);
+    if let Some(mapping) = MAPPING.read().unwrap().as_ref() {
+        if mapping.randomize_skills {
+            skill_manager::set_skills(&ARCHIPELAGO_DATA.read().unwrap());
+        }
+        if mapping.progressive_difficulty {
+            // Don't let vanilla unlocks stick around
+            apply_difficulty_unlocks(mapping, &ARCHIPELAGO_DATA.read().unwrap());
+        }
+        door_randomizer::on_room_loaded(mapping);
     }
     res
 }
@@ -695,22 +795,23 @@ pub fn set_player_data(param_1: usize) -> bool {
             skill_manager::set_skills(&ARCHIPELAGO_DATA.read().unwrap());
         }
         if mapping.randomize_styles {
-            game_manager::set_style_levels()
+            game_manager::set_style_levels();
+            game_manager::validate_equipped_style();
+        }
+        let data = ARCHIPELAGO_DATA.read().unwrap();
+        apply_costume_unlocks(mapping, &data);
+        if mapping.random_costume {
+            randomize_costume(mapping, &data);
         }
     }
     LAST_OBTAINED_ID.store(0, Ordering::SeqCst); // Should stop random item jumpscares
-    run_scouts_for_mission(
-        AP_CORE
-            .get()
-            .unwrap()
-            .lock()
-            .unwrap()
-            .connection
-            .client_mut()
-            .unwrap(),
-        get_mission(),
-        CreateAsHint::No,
-    );
+    text_handler::set_display_graphic(None);
+    {
+        let mut core = AP_CORE.get().unwrap().lock().unwrap();
+        let client = core.connection.client_mut().unwrap();
+        run_scouts_for_mission(client, get_mission(), CreateAsHint::No);
+        checklist::refresh(client, get_mission());
+    }
     unsafe {
         if let Some(original) = ORIGINAL_SETUP_PLAYER_DATA.get() {
             res = original(param_1)
@@ -741,13 +842,86 @@ pub const SKILL_SHOP_ADDR: usize = 0x288280;
 pub static ORIGINAL_SKILL_SHOP: OnceLock<unsafe extern "C" fn(custom_skill: usize)> =
     OnceLock::new();
 
-pub fn deny_skill_purchasing(custom_skill: usize) {
+/// Expertise with the checked shop slots bought, taken when the shop is first opened in a room
+static SKILL_SHOP_CHECKS: Mutex<Option<[u32; 8]>> = Mutex::new(None);
+
+fn clear_skill_shop_checks() {
+    if let Ok(mut checks) = SKILL_SHOP_CHECKS.lock() {
+        *checks = None;
+    }
+}
+
+/// The cached skill checks, falling back to `backup` if they can't be fetched yet
+fn get_skill_shop_checks(backup: [u32; 8]) -> [u32; 8] {
+    let Ok(mut cached) = SKILL_SHOP_CHECKS.lock() else {
+        log::error!("Failed to lock the skill shop checks");
+        return backup;
+    };
+    if let Some(checks) = *cached {
+        return checks;
+    }
+    if let Some(core) = AP_CORE.get()
+        && let Ok(core) = core.lock()
+    {
+        if let Some(client) = core.connection.client() {
+            let checks = skill_manager::get_skill_purchase_checks(client);
+            *cached = Some(checks);
+            return checks;
+        }
+    } else {
+        log::error!("Failed to lock AP_CORE for the skill shop");
+    }
+    backup
+}
+
+pub fn skill_shop(custom_skill: usize) {
+    auto_hint::on_shop_visit();
+    // TODO Vergil's shop stays vanilla until his expertise flags are mapped out, there's no skill checks for him
     if let Some(mapping) = MAPPING.read().unwrap().as_ref()
         && mapping.randomize_skills
+        && !game_manager::playing_vergil()
     {
-        CANT_PURCHASE.store(true, Ordering::SeqCst);
-        if read_data_from_address::<u8>(custom_skill + 0x08) == 0x05 {
-            //unsafe { replace_single_byte(custom_skill + 0x08, 0x01) }
+        if !mapping.shop_skill_checks {
+            CANT_PURCHASE.store(true, Ordering::SeqCst);
+            if read_data_from_address::<u8>(custom_skill + 0x08) == 0x05 {
+                //unsafe { replace_single_byte(custom_skill + 0x08, 0x01) }
+            }
+        } else {
+            // Same idea as the gun shop, the shop displays the checked slots as bought
+            let backup_expertise = skill_manager::read_expertise();
+            let skill_checks = get_skill_shop_checks(backup_expertise);
+            skill_manager::write_expertise(skill_checks);
+            if let Some(orig) = ORIGINAL_SKILL_SHOP.get() {
+                unsafe {
+                    orig(custom_skill);
+                }
+            }
+            let expertise_new = skill_manager::read_expertise();
+            let newly_purchased =
+                skill_manager::get_newly_purchased_skills(&skill_checks, &expertise_new);
+            if !newly_purchased.is_empty()
+                && let Ok(mut cached) = SKILL_SHOP_CHECKS.lock()
+                && cached.is_some()
+            {
+                // Keep showing the slot as bought while the shop stays open
+                *cached = Some(expertise_new);
+            }
+            for skill_id in newly_purchased {
+                log::debug!("Attempting to purchase skill: {:#X}", skill_id);
+                check_handler::send_off_location_coords(
+                    Location {
+                        location_type: LocationType::PurchaseItem,
+                        item_id: skill_id as u32,
+                        mission: 0,
+                        room: 0,
+                        coordinates: EMPTY_COORDINATES,
+                    },
+                    u32::MAX,
+                );
+            }
+            // Only the skills we've received should actually be usable
+            skill_manager::write_expertise(backup_expertise);
+            return;
         }
     }
 
@@ -761,6 +935,7 @@ pub fn deny_skill_purchasing(custom_skill: usize) {
 pub const GUN_SHOP_ADDR: usize = 0x283d60;
 pub static ORIGINAL_GUN_SHOP: OnceLock<unsafe extern "C" fn(custom_gun: usize)> = OnceLock::new();
 pub fn gun_upgrade(custom_gun: usize) {
+    auto_hint::on_shop_visit();
     // Get all current gun levels
     if let Some(mapping) = MAPPING.read().unwrap().as_ref() {
         let backup_gun_levels = read_data_from_address::<[u8; 5]>(custom_gun + 0x3D10);
@@ -876,10 +1051,18 @@ pub fn modify_available_styles(data_ptr: usize) -> bool {
             // Trick, Sword, Gun, Royal
             match ARCHIPELAGO_DATA.read() {
                 Ok(data) => {
+                    let unlocked = data.get_style_unlocked();
                     write(
                         (data_ptr + 0x98C6) as *mut [bool; 4],
-                        data.get_style_unlocked(),
+                        [unlocked[0], unlocked[1], unlocked[2], unlocked[3]],
                     );
+                    for style in [Style::Quicksilver, Style::Doppelganger] {
+                        set_item(
+                            style.get_item_name().unwrap(),
+                            unlocked[style.index()],
+                            false,
+                        );
+                    }
                 }
                 Err(err) => {
                     log::error!("Failed to get ArchipelagoData: {}", err)
@@ -887,12 +1070,22 @@ pub fn modify_available_styles(data_ptr: usize) -> bool {
             }
         }
     }
+    let mut res = false;
     if let Some(orig) = ORIGINAL_STYLE_MENU.get() {
         unsafe {
-            return orig(data_ptr);
+            res = orig(data_ptr);
         }
     }
-    false
+    // The statue is the only place to change styles mid-mission, don't leave with a locked one
+    if MAPPING
+        .read()
+        .unwrap()
+        .as_ref()
+        .is_some_and(|mapping| mapping.randomize_styles)
+    {
+        game_manager::validate_equipped_style();
+    }
+    res
 }
 
 pub const GIVE_STYLE_XP: usize = 0x1fa2c0;
@@ -912,6 +1105,23 @@ pub fn give_no_xp(param_1: usize, xp_amount: f32) -> f32 {
     }
 }
 
+const DIFFICULTY_UNLOCKS: usize = 0x564594;
+
+/// Write out the unlocked difficulties, only the initial ones unless difficulties are items
+pub(crate) fn apply_difficulty_unlocks(mapping: &Mapping, data: &ArchipelagoData) {
+    let difficulties = if mapping.progressive_difficulty {
+        data.get_unlocked_difficulties(&mapping.initially_unlocked_difficulties)
+    } else {
+        mapping.initially_unlocked_difficulties.clone()
+    };
+    unsafe {
+        replace_single_byte(
+            *DMC3_ADDRESS + DIFFICULTY_UNLOCKS,
+            DifficultyUnlockFlags::create_final_flag(&difficulties).bits(),
+        );
+    }
+}
+
 pub const SET_NEW_SESSION_DATA: usize = 0x212760; //0x242cc0; // Use current address to have compat with crimson
 pub static ORIGINAL_SET_NEW_SESSION_DATA: OnceLock<unsafe extern "C" fn(ptr: usize) -> f32> =
     OnceLock::new();
@@ -929,7 +1139,7 @@ bitflags! {
 }
 
 impl DifficultyUnlockFlags {
-    fn create_final_flag(difficulties: &Vec<Difficulty>) -> DifficultyUnlockFlags {
+    fn create_final_flag(difficulties: &[Difficulty]) -> DifficultyUnlockFlags {
         let mut res = DifficultyUnlockFlags::empty();
         for difficulty in difficulties {
             res = res.union(match difficulty {
@@ -975,7 +1185,7 @@ impl UnlockFlags {
 }
 
 bitflags! {
-    #[derive(Debug)]
+    #[derive(Debug, Clone, Copy)]
     struct CostumeFlags: u8 {
         const SuperCorruptVergil = 0b10000000;
         const CorruptVergil = 0b01000000;
@@ -989,20 +1199,76 @@ bitflags! {
 }
 
 impl CostumeFlags {
-    fn create_final_flag() -> CostumeFlags {
-        let mut res = CostumeFlags::empty();
-        //res = res.union(CostumeFlags::SuperCorruptVergil);
-        res = res.union(CostumeFlags::CorruptVergil);
-        //res = res.union(CostumeFlags::SuperVergil);
-        res = res.union(CostumeFlags::CoatlessVergil);
-        //res = res.union(CostumeFlags::SuperSparda);
-        res = res.union(CostumeFlags::Sparda);
-        //res = res.union(CostumeFlags::SuperDante);
-        res = res.union(CostumeFlags::CoatlessDMC1Dante);
+    /// Infinite DT costumes, only given out once the goal is reached
+    const SUPER_COSTUMES: CostumeFlags = CostumeFlags::SuperCorruptVergil
+        .union(CostumeFlags::SuperVergil)
+        .union(CostumeFlags::SuperSparda)
+        .union(CostumeFlags::SuperDante);
+
+    fn create_final_flag(mapping: &Mapping, data: &ArchipelagoData) -> CostumeFlags {
+        let mut res = if mapping.costume_items {
+            CostumeFlags::from_bits_truncate(data.get_unlocked_costumes())
+        } else {
+            CostumeFlags::all()
+        };
+        if !goal::is_goal_reached() {
+            res.remove(CostumeFlags::SUPER_COSTUMES);
+        }
         res
     }
 }
 
+const COSTUME_UNLOCKS: usize = 0x564596;
+
+/// Costumes in the order they appear in costume select, along with the flag needed to use them
+const DANTE_COSTUMES: [(u8, CostumeFlags); 8] = [
+    (0, CostumeFlags::empty()),
+    (1, CostumeFlags::empty()),
+    (2, CostumeFlags::empty()),
+    (3, CostumeFlags::empty()),
+    (4, CostumeFlags::CoatlessDMC1Dante),
+    (5, CostumeFlags::Sparda),
+    (6, CostumeFlags::SuperDante),
+    (7, CostumeFlags::SuperSparda),
+];
+const VERGIL_COSTUMES: [(u8, CostumeFlags); 5] = [
+    (0, CostumeFlags::empty()),
+    (1, CostumeFlags::CoatlessVergil),
+    (2, CostumeFlags::SuperVergil),
+    (3, CostumeFlags::CorruptVergil),
+    (4, CostumeFlags::SuperCorruptVergil),
+];
+
+/// Write out the unlocked costumes, super costumes are held back until the goal is reached
+pub(crate) fn apply_costume_unlocks(mapping: &Mapping, data: &ArchipelagoData) {
+    unsafe {
+        replace_single_byte(
+            *DMC3_ADDRESS + COSTUME_UNLOCKS,
+            CostumeFlags::create_final_flag(mapping, data).bits(),
+        );
+    }
+}
+
+/// Pick a random costume out of the unlocked ones for the current character
+fn randomize_costume(mapping: &Mapping, data: &ArchipelagoData) {
+    let unlocked = CostumeFlags::create_final_flag(mapping, data);
+    let costumes: &[(u8, CostumeFlags)] = if mapping.character == Character::Vergil {
+        &VERGIL_COSTUMES
+    } else {
+        &DANTE_COSTUMES
+    };
+    if let Some((costume, _)) = costumes
+        .iter()
+        .filter(|(_, flag)| unlocked.contains(*flag))
+        .choose(&mut rand::rng())
+    {
+        log::debug!("Using costume {}", costume);
+        if with_session(|s| s.costume = *costume).is_err() {
+            log::warn!("Failed to set costume");
+        }
+    }
+}
+
 // Could do gallery here, but I see no reason to
 
 pub fn set_rando_session_data(ptr: usize) {
@@ -1013,28 +1279,25 @@ pub fn set_rando_session_data(ptr: usize) {
     }
     log::debug!("Starting new game, setting appropriate data");
     with_session(|s| {
-        if s.char != Character::Dante as u8 {
-            log::error!(
-                "Character is {} not Dante",
-                Character::from_repr(s.char as usize).unwrap()
-            );
-            log::info!("Only Dante is supported at the moment");
-            return;
-        }
         if let Some(mapping) = MAPPING.read().unwrap().as_ref() {
+            if s.char != mapping.character as u8 {
+                log::error!(
+                    "Character is {} but the room was generated for {}",
+                    Character::from_repr(s.char as usize).unwrap(),
+                    mapping.character
+                );
+                return;
+            }
             // Unlock difficulties, costumes and modes
+            apply_difficulty_unlocks(mapping, &ARCHIPELAGO_DATA.read().unwrap());
             unsafe {
-                let difficulty_flags = DifficultyUnlockFlags::create_final_flag(
-                    &mapping.initially_unlocked_difficulties,
-                );
                 let unlock_flags = UnlockFlags::create_final_flag();
-                let costume_flags = CostumeFlags::create_final_flag();
-                replace_single_byte(*DMC3_ADDRESS + 0x564594, difficulty_flags.bits());
                 replace_single_byte(*DMC3_ADDRESS + 0x564595, unlock_flags.bits());
-                replace_single_byte(*DMC3_ADDRESS + 0x564596, costume_flags.bits());
             }
-            // Set initial style if relevant
+            apply_costume_unlocks(mapping, &ARCHIPELAGO_DATA.read().unwrap());
+            // Set initial style if relevant, Vergil only has Dark Slayer
             if mapping.randomize_styles
+                && mapping.character == Character::Dante
                 && let Some(index) = ARCHIPELAGO_DATA
                     .read()
                     .unwrap()
@@ -1049,15 +1312,21 @@ pub fn set_rando_session_data(ptr: usize) {
             // Set starter weapons
             s.weapons[0] = mapping.start_melee;
             s.weapons[1] = mapping.start_second_melee;
-            s.weapons[2] = mapping.start_gun;
-            s.weapons[3] = mapping.start_second_gun;
+            if mapping.character == Character::Vergil {
+                // No guns for Vergil
+                s.weapons[2] = 0xFF;
+                s.weapons[3] = 0xFF;
+            } else {
+                s.weapons[2] = mapping.start_gun;
+                s.weapons[3] = mapping.start_second_gun;
+            }
 
             // Unlock DT off the bat
             s.unlocked_dt = true;
             /* Should see if I can change unlocked files? Or unlock them all.
             Game seemed to just auto unlock them though when the weapon is used
             Overall, not too important */
-            s.red_orbs = 0;
+            s.red_orbs = mapping.starting_red_orbs.max(0);
             #[cfg(debug_assertions)]
             {
                 // Give max red orbs if we are using a debug build
@@ -1083,16 +1352,30 @@ pub fn set_rando_session_data(ptr: usize) {
     match AP_CORE.get().unwrap().lock() {
         Ok(mut core) => {
             CURRENT_INDEX.store(0, Ordering::SeqCst);
+            archipelago::GRANT_STARTER_ITEMS.store(true, Ordering::SeqCst);
             if let Err(e) =
                 archipelago::handle_received_items_packet(0, core.connection.client_mut().unwrap())
             {
                 log::error!("Failed to handle received items: {:?}", e);
             }
+            // Only meant for this new game, don't let it carry over if the starting state wasn't redone
+            archipelago::GRANT_STARTER_ITEMS.store(false, Ordering::SeqCst);
         }
         Err(err) => {
             log::error!("Error locking core: {}", err);
         }
     }
+    // Keys come from items, so the first mission can only be picked once they've been handled
+    if let Some(mapping) = MAPPING.read().unwrap().as_ref()
+        && mapping.goal == Goal::MissionKeys
+    {
+        let first = get_unlocked_missions()[0];
+        with_session(|s| {
+            s.mission = first;
+            s.other_mission = first;
+        })
+        .unwrap();
+    }
 }
 
 pub const SELECT_MISSION_BUTTON: usize = 0x29a7b0;
@@ -1108,20 +1391,41 @@ pub fn rewrite_mission_order(ptr: usize) {
         }
     }
     if let Some(mapping) = MAPPING.read().unwrap().as_ref() {
+        if mapping.goal == Goal::MissionKeys {
+            update_mission_select_preview(ptr);
+        }
         match val {
             0 => {
                 if mapping.goal != Goal::Standard {
                     with_session(|s| {
+                        let index = if mapping.goal == Goal::MissionKeys {
+                            get_unlocked_missions()
+                                .iter()
+                                .position(|m| *m == s.mission)
+                                .unwrap_or_default()
+                        } else {
+                            mapping.get_index_for_mission(s.mission)
+                        };
                         // Sets the mission selected on the select screen to be the correct one
                         unsafe {
                             replace_single_byte(
                                 ptr + 0x628A + s.difficulty as usize,
-                                1 + mapping.get_index_for_mission(s.mission) as u8,
+                                1 + index as u8,
                             );
                         }
                     })
                     .unwrap();
+                    let unlocked = ARCHIPELAGO_DATA
+                        .read()
+                        .unwrap()
+                        .get_unlocked_difficulties(&mapping.initially_unlocked_difficulties);
                     for difficulty in 0..6 {
+                        if mapping.progressive_difficulty
+                            && !unlocked.contains(&Difficulty::from_repr(difficulty).unwrap())
+                        {
+                            // Locked difficulties keep whatever the game had
+                            continue;
+                        }
                         let max_mission = calculate_max_mission(
                             mapping,
                             Difficulty::from_repr(difficulty).unwrap(),
@@ -1138,14 +1442,32 @@ pub fn rewrite_mission_order(ptr: usize) {
                 }
             }
             6 => {
+                if mapping.goal == Goal::MissionKeys {
+                    // The select screen only lists unlocked missions, map the selection back to the mission
+                    let unlocked = get_unlocked_missions();
+                    with_session(|s| {
+                        let mission = (s.mission as usize)
+                            .checked_sub(1)
+                            .and_then(|i| unlocked.get(i))
+                            .or(unlocked.last())
+                            .copied()
+                            .unwrap_or(1);
+                        s.mission = mission;
+                        s.other_mission = mission;
+                    })
+                    .expect("Unable to edit session data");
+                }
                 if mapping.goal == Goal::RandomOrder {
                     with_session(|s| {
                         log::debug!("Original Mission was: {}", s.mission);
                         log::debug!("Original O Mission was: {}", s.other_mission);
-                        if let Some(mission_order) = &mapping.mission_order {
-                            let mission_idx = s.mission as usize;
-                            s.mission = mission_order[mission_idx - 1] as u32;
-                            s.other_mission = mission_order[mission_idx - 1] as u32;
+                        if let Some(mission) = mapping.mission_order.as_ref().and_then(|order| {
+                            (s.mission as usize)
+                                .checked_sub(1)
+                                .and_then(|i| order.get(i))
+                        }) {
+                            s.mission = *mission as u32;
+                            s.other_mission = *mission as u32;
                         }
                     })
                     .expect("Unable to edit session data");
@@ -1156,6 +1478,20 @@ pub fn rewrite_mission_order(ptr: usize) {
     }
 }
 
+/// The select screen counts through the unlocked missions, show which mission the highlighted slot really is
+fn update_mission_select_preview(ptr: usize) {
+    let Ok(difficulty) = with_session_read(|s| s.difficulty) else {
+        return;
+    };
+    let slot = read_data_from_address::<u8>(ptr + 0x628A + difficulty as usize);
+    let unlocked = get_unlocked_missions();
+    if let Some(mission) = (slot as usize).checked_sub(1).and_then(|i| unlocked.get(i))
+        && let Ok(mut preview) = MISSION_SELECT_PREVIEW.write()
+    {
+        *preview = Some((format!("Mission #{}", mission), Instant::now()));
+    }
+}
+
 fn calculate_max_mission(mapping: &Mapping, difficulty: Difficulty) -> u8 {
     match mapping.goal {
         Goal::RandomOrder => {
@@ -1191,6 +1527,17 @@ fn calculate_max_mission(mapping: &Mapping, difficulty: Difficulty) -> u8 {
             1
         }
         Goal::All => 20,
+        Goal::MissionKeys => get_unlocked_missions().len() as u8,
+    }
+}
+
+fn get_unlocked_missions() -> Vec<u32> {
+    match ARCHIPELAGO_DATA.read() {
+        Ok(data) => data.get_unlocked_missions(),
+        Err(err) => {
+            log::error!("Failed to read data from ARCHIPELAGO_DATA: {}", err);
+            vec![1]
+        }
     }
 }
 
@@ -1208,10 +1555,10 @@ fn set_actual_mission(cscene_result: usize, param_1: usize, param_2: usize, para
         panic!("Failed to find original method for result screen");
     };
     if let Some(mapping) = MAPPING.read().unwrap().as_ref()
-        && mapping.goal == Goal::RandomOrder
+        && val == 0x12
     {
-        match val {
-            0x12 => {
+        match mapping.goal {
+            Goal::RandomOrder => {
                 with_session(|s| {
                     s.mission = mapping.mission_order.as_ref().unwrap()
                         [min(mapping.get_index_for_mission(current_mission) + 1, 19)]
@@ -1219,7 +1566,20 @@ fn set_actual_mission(cscene_result: usize, param_1: usize, param_2: usize, para
                 })
                 .unwrap();
             }
-            _ => {}
+            Goal::MissionKeys => {
+                // Next unlocked mission, or stay put if there isn't one
+                let unlocked = get_unlocked_missions();
+                let next = unlocked
+                    .iter()
+                    .find(|m| **m > current_mission)
+                    .copied()
+                    .unwrap_or(current_mission);
+                with_session(|s| {
+                    s.mission = next;
+                })
+                .unwrap();
+            }
+            Goal::Standard | Goal::All => {}
         }
     }
     res
@@ -12,12 +12,16 @@ use windows::Win32::Foundation::*;
 use windows::core::BOOL;
 
 mod archipelago;
+mod auto_hint;
 mod check_handler;
 mod compat;
 mod config;
 mod constants;
 mod data;
+mod door_randomizer;
+mod enemy_randomizer;
 mod game_manager;
+mod goal;
 mod hint_game;
 mod hook;
 mod location_handler;
@@ -119,6 +123,7 @@ fn main_loop_hook() {
         && let Err(err) = core.update()
     {
         log::error!("{}", err);
+        core.disconnect();
         log::debug!("Attempting to reconnect");
         core.connection = Connection::new(
             config::CONFIG.connections.get_url(),
@@ -2,6 +2,9 @@ use crate::check_handler::{Location, LocationType};
 use crate::constants::{DUMMY_ID, EVENT_TABLES, EventCode, ITEM_MAP, ItemCategory, REMOTE_ID};
 use crate::data::generated_locations;
 use crate::game_manager::get_mission;
+use crate::skill_manager::ID_SKILL_MAP;
+use crate::ui::text_handler;
+use crate::ui::text_handler::DisplayItem;
 use crate::{constants, game_manager, utilities};
 use anyhow::anyhow;
 use randomizer_utilities::archipelago_utilities;
@@ -50,10 +53,17 @@ pub fn get_location_name_by_data(location_data: &Location) -> Result<&'static st
                                     0x1E => format!("Artemis Level {}", location_data.mission),
                                     0x1F => format!("Spiral Level {}", location_data.mission),
                                     0x21 => format!("Kalina Ann Level {}", location_data.mission),
+                                    0x40..0x53 => ID_SKILL_MAP
+                                        .get(&(location_data.item_id as usize))
+                                        .unwrap()
+                                        .to_string(),
                                     _ => unreachable!(),
                                 }
                             )
                     }
+                    LocationType::BloodyPalaceFloor => {
+                        *(*key) == format!("Bloody Palace Floor {}", location_data.mission).as_str()
+                    }
                 })
     {
         return Ok(location.0);
@@ -114,7 +124,8 @@ pub fn get_mapped_item_id(location_name: &str) -> Result<u32, Box<dyn Error>> {
             0x55 => *ITEM_MAP.get_by_left("Artemis").unwrap(),
             0x56 => *ITEM_MAP.get_by_left("Spiral").unwrap(),
             0x57 => *ITEM_MAP.get_by_left("Kalina Ann").unwrap(),
-            // It would be neat to have custom pics for styles...
+            // Styles and Vergil's gear have no pickup of their own
+            (0x60..0x68) => *REMOTE_ID,
             _ => {
                 log::error!("Unrecognized id {}, default to Remote", id);
                 *REMOTE_ID
@@ -124,6 +135,19 @@ pub fn get_mapped_item_id(location_name: &str) -> Result<u32, Box<dyn Error>> {
     Ok(id)
 }
 
+/// Graphic to draw on the item get screen for items that are given as Remote, see get_mapped_item_id
+pub fn get_display_graphic(location_name: &str) -> Option<u32> {
+    let cached_locations = archipelago_utilities::CACHED_LOCATIONS.read().ok()?;
+    let located_item = cached_locations.get(location_name)?;
+    if located_item.sender() != located_item.receiver() {
+        return Some(text_handler::get_remote_item_graphic(located_item));
+    }
+    match located_item.item().id() {
+        0x60..0x65 => Some(text_handler::get_item_graphic(DisplayItem::Style)),
+        _ => None,
+    }
+}
+
 pub fn edit_end_event(location_key: &str) {
     match EVENT_TABLES.get(&get_mission()) {
         None => {}
@@ -1,5 +1,6 @@
-use crate::constants::{Difficulty, Rank};
+use crate::constants::{Character, Difficulty, Rank};
 use crate::data::generated_locations;
+use crate::goal::GoalCondition;
 use archipelago_rs::{Client, CreateAsHint, Location};
 use randomizer_utilities::{APVersion, archipelago_utilities};
 use serde::{Deserialize, Deserializer, Serialize};
@@ -64,6 +65,10 @@ fn default_goal() -> Goal {
     Goal::Standard
 }
 
+fn default_scale() -> f32 {
+    1.0
+}
+
 fn default_difficulty_list() -> Vec<Difficulty> {
     vec![Difficulty::Easy, Difficulty::Normal]
 }
@@ -102,6 +107,7 @@ where
             0 => Ok(Goal::Standard),
             1 => Ok(Goal::All),
             2 => Ok(Goal::RandomOrder),
+            3 => Ok(Goal::MissionKeys),
             _ => Err(serde::de::Error::custom(format!(
                 "Invalid goal option: {}",
                 n
@@ -156,6 +162,28 @@ where
     }
 }
 
+/// Parse which character the room was generated for
+fn parse_character<'de, D>(deserializer: D) -> Result<Character, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let val = Value::deserialize(deserializer)?;
+    match val {
+        Value::Number(n) => match n.as_i64().unwrap_or_default() {
+            0 => Ok(Character::Dante),
+            1 => Ok(Character::Vergil),
+            _ => Err(serde::de::Error::custom(format!(
+                "Invalid character option: {}",
+                n
+            ))),
+        },
+        other => Err(serde::de::Error::custom(format!(
+            "Unexpected type: {:?}",
+            other
+        ))),
+    }
+}
+
 fn parse_hint<'de, D>(deserializer: D) -> Result<AutoHint, D::Error>
 where
     D: Deserializer<'de>,
@@ -202,6 +230,9 @@ pub struct Mapping {
     // For mapping JSON
     pub starter_items: Vec<String>,
     pub adjudicators: Option<HashMap<String, AdjudicatorData>>,
+    #[serde(default)]
+    #[serde(deserialize_with = "parse_character")]
+    pub character: Character,
     pub start_melee: u8,
     pub start_second_melee: u8,
     pub start_gun: u8,
@@ -209,6 +240,9 @@ pub struct Mapping {
     pub randomize_skills: bool,
     pub randomize_gun_levels: bool,
     pub randomize_styles: bool,
+    /// Extra copies of Quicksilver and Doppelganger level them up
+    #[serde(default)]
+    pub progressive_styles: bool,
     pub purple_orb_mode: bool,
     pub devil_trigger_mode: bool,
     pub enabled_ss_rank: bool,
@@ -217,7 +251,7 @@ pub struct Mapping {
     #[serde(default)]
     pub shop_gun_checks: bool,
     #[serde(default)]
-    pub shop_skill_checks: bool,
+    pub shop_skill_checks: bool, // Dante only, Vergil's shop isn't randomized yet
     #[serde(deserialize_with = "parse_hint")]
     pub auto_orb_hints: AutoHint,
     #[serde(default)]
@@ -231,6 +265,9 @@ pub struct Mapping {
     #[serde(default = "default_goal")]
     #[serde(deserialize_with = "parse_goal")]
     pub goal: Goal,
+    /// Full goal, if not present then only the missions required by goal are needed
+    #[serde(default)]
+    pub goal_conditions: Option<GoalCondition>,
     pub mission_order: Option<Vec<u8>>,
     pub generated_version: Option<APVersion>,
     pub client_version: Option<APVersion>,
@@ -242,6 +279,38 @@ pub struct Mapping {
     pub mission_clear_difficulty: Difficulty,
     #[serde(default = "default_difficulty_list")]
     pub initially_unlocked_difficulties: Vec<Difficulty>,
+    /// Difficulties past the initial ones are unlocked by items rather than the vanilla way
+    #[serde(default)]
+    pub progressive_difficulty: bool,
+    /// Send a check every N Bloody Palace floors, rounded up to a multiple of 100 on connect. 0 to disable
+    #[serde(default)]
+    pub bp_floors_per_check: u16,
+    /// Bloody Palace boss floors (Including 9999) are checks
+    #[serde(default)]
+    pub bp_boss_checks: bool,
+    /// Seed for the enemy randomizer, None if it's disabled
+    #[serde(default)]
+    pub enemy_randomizer_seed: Option<u64>,
+    /// Doors to redirect for the door randomizer, None if it's disabled
+    #[serde(default)]
+    pub door_connections: Option<Vec<DoorConnection>>,
+    /// Red orbs given by each of the Red Orb filler items (IDs 1-3), defaults to 1000/2500/5000
+    #[serde(default)]
+    pub red_orb_filler_values: Option<Vec<i32>>,
+    /// Multiplier for red orbs picked up in missions
+    #[serde(default = "default_scale")]
+    pub red_orb_multiplier: f32,
+    /// Multiplier for the red orb cost of shop purchases
+    #[serde(default = "default_scale")]
+    pub shop_price_scale: f32,
+    #[serde(default)]
+    pub starting_red_orbs: i32,
+    /// Costumes are unlocked by items rather than all being available from the start
+    #[serde(default)]
+    pub costume_items: bool,
+    /// Use a random unlocked costume for each mission
+    #[serde(default)]
+    pub random_costume: bool,
 }
 
 impl Mapping {
@@ -268,6 +337,8 @@ pub enum Goal {
     All,
     /// Beat all missions in a randomized linear order
     RandomOrder,
+    /// Beat all missions, each one is unlocked by its key
+    MissionKeys,
 }
 
 #[derive(Deserialize, Serialize, Clone, Debug)]
@@ -281,6 +352,28 @@ pub enum DeathlinkSetting {
 pub struct AdjudicatorData {
     pub weapon: String,
     pub ranking: u8,
+    /// Rank required on specific difficulties, keyed by difficulty name. Falls back to ranking
+    #[serde(default)]
+    pub difficulty_rankings: HashMap<String, u8>,
+}
+
+impl AdjudicatorData {
+    pub(crate) fn get_ranking(&self, difficulty: Difficulty) -> u8 {
+        self.difficulty_rankings
+            .get(&difficulty.to_string())
+            .copied()
+            .unwrap_or(self.ranking)
+    }
+}
+
+/// A door that leads somewhere else, the destination is stored in the mission's event table
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct DoorConnection {
+    pub mission: u32,
+    pub room: i32,
+    /// Offset of the door's destination room in the event table
+    pub offset: usize,
+    pub target: i32,
 }
 
 pub fn run_scouts_for_mission(client: &mut Client<ModModeData>, mission: u32, hint: CreateAsHint) {
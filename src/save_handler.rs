@@ -1,7 +1,7 @@
 use crate::archipelago::CONNECTED;
 use crate::game_manager::{ARCHIPELAGO_DATA, ArchipelagoData};
 use crate::utilities::DMC3_ADDRESS;
-use crate::{AP_CORE, archipelago, create_hook, utilities};
+use crate::{AP_CORE, archipelago, create_hook, goal, utilities};
 use minhook::MH_STATUS;
 use minhook::MinHook;
 use randomizer_utilities::item_sync;
@@ -160,12 +160,17 @@ fn load_slot(param_1: usize, save_index: i32) {
                     CURRENT_INDEX
                         .store(sync_data.sync_index[save_index as usize], Ordering::SeqCst);
                     *ARCHIPELAGO_DATA.write().unwrap() = ArchipelagoData::default();
+                    // A loaded save already had its starter items
+                    archipelago::GRANT_STARTER_ITEMS.store(false, Ordering::SeqCst);
                     if let Err(e) = archipelago::handle_received_items_packet(
                         sync_data.sync_index[save_index as usize] as usize,
                         client,
                     ) {
                         log::error!("Failed to handle received items: {:?}", e);
                     }
+                    if let Err(e) = goal::check_goal(client) {
+                        log::error!("Failed to check goal: {:?}", e);
+                    }
                 }
 
                 Err(err) => {
@@ -1,6 +1,8 @@
 use crate::game_manager::ArchipelagoData;
+use crate::mapping::ModModeData;
 use crate::{game_manager, utilities};
-use std::collections::HashMap;
+use archipelago_rs::Client;
+use std::collections::{HashMap, HashSet};
 use std::ops::BitOrAssign;
 use std::ptr::{read_unaligned, write_unaligned};
 
@@ -31,6 +33,12 @@ pub static ID_SKILL_MAP: LazyLock<HashMap<usize, &'static str>> = LazyLock::new(
         (0x62, "Progressive Gunslinger"),
         (0x63, "Progressive Royalguard"),
     ]));
+    map.extend(HashMap::from([
+        (0x64, "Progressive Dark Slayer"),
+        (0x65, "Yamato"),
+        (0x66, "Beowulf (Vergil)"),
+        (0x67, "Force Edge"),
+    ]));
     map
 });
 
@@ -196,6 +204,10 @@ static DEFAULT_SKILLS: [u32; 8] = [
 ];
 
 pub(crate) fn reset_expertise() {
+    if game_manager::playing_vergil() {
+        // TODO Vergil's expertise flags still need to be mapped out, leave his moves alone until then
+        return;
+    }
     game_manager::with_session(|s| {
         s.expertise = DEFAULT_SKILLS;
     })
@@ -215,6 +227,10 @@ const EXPERTISE_OFFSET: usize = 0x3FEC; // 0x400c
 fn give_skill(skill_id: &usize) {
     // This works, might not update files? need to double-check
     let data = SKILLS_MAP.get(ID_SKILL_MAP.get(skill_id).unwrap()).unwrap();
+    if game_manager::playing_vergil() {
+        // Dante's skill flags mean nothing to Vergil
+        return;
+    }
     game_manager::with_session(|s| {
         s.expertise[data.index].bitor_assign(data.flag);
     })
@@ -241,6 +257,76 @@ pub(crate) fn set_skills(data: &ArchipelagoData) {
     }
 }
 
+/// Names of the purchasable skills for a weapon
+pub(crate) fn get_skill_names_for_weapon(weapon: &str) -> Vec<&'static str> {
+    let prefix = format!("{} - ", weapon);
+    let mut skills: Vec<&'static str> = SKILLS_MAP
+        .keys()
+        .filter(|name| name.starts_with(&prefix))
+        .copied()
+        .collect();
+    skills.sort_by_key(|name| SKILLS_MAP.get(name).unwrap().id);
+    skills
+}
+
+/// The skill that has to be bought before this one shows up in the shop
+pub(crate) fn get_skill_prerequisite(skill_name: &str) -> Option<&'static str> {
+    match skill_name {
+        "Rebellion - Stinger Level 2" => Some("Rebellion - Stinger Level 1"),
+        "Agni and Rudra - Jet Stream Level 3" => Some("Agni and Rudra - Jet Stream Level 2"),
+        "Nevan - Reverb Shock Level 2" => Some("Nevan - Reverb Shock"),
+        "Beowulf - Rising Dragon" => Some("Beowulf - Beast Uppercut"),
+        _ => None,
+    }
+}
+
+pub(crate) fn get_purchase_location_name(skill_name: &str) -> String {
+    format!("Purchase {}", skill_name)
+}
+
+/// Session expertise as it would be if only the shop slots that have been checked were bought
+pub(crate) fn get_skill_purchase_checks(client: &Client<ModModeData>) -> [u32; 8] {
+    let checked: HashSet<String> = client
+        .checked_locations()
+        .map(|loc| loc.name().to_string())
+        .collect();
+    let mut expertise = DEFAULT_SKILLS;
+    for (name, data) in SKILLS_MAP.iter() {
+        if checked.contains(&get_purchase_location_name(name)) {
+            expertise[data.index].bitor_assign(data.flag);
+        }
+    }
+    expertise
+}
+
+/// IDs of the skills whose flags are set in `after` but not in `before`
+pub(crate) fn get_newly_purchased_skills(before: &[u32; 8], after: &[u32; 8]) -> Vec<usize> {
+    SKILLS_MAP
+        .values()
+        .filter(|data| before[data.index] & data.flag == 0 && after[data.index] & data.flag != 0)
+        .map(|data| data.id)
+        .collect()
+}
+
+pub(crate) fn read_expertise() -> [u32; 8] {
+    game_manager::with_session_read(|s| s.expertise).unwrap_or(DEFAULT_SKILLS)
+}
+
+/// Overwrite expertise for both the session and the active character
+pub(crate) fn write_expertise(expertise: [u32; 8]) {
+    if game_manager::with_session(|s| s.expertise = expertise).is_err() {
+        log::warn!("Failed to write session expertise");
+    }
+    if let Some(char_data_ptr) = utilities::get_active_char_address() {
+        unsafe {
+            write_unaligned(
+                (char_data_ptr + EXPERTISE_OFFSET) as *mut [u32; 8],
+                expertise,
+            )
+        }
+    }
+}
+
 // Certain skills have two levels they can gain
 pub(crate) fn add_skill(id: usize, data: &mut ArchipelagoData) {
     match id {
@@ -0,0 +1,263 @@
+use crate::door_randomizer;
+use crate::ui::overlay::{MessageSegment, get_color_for_item, get_default_color};
+use crate::ui::{checklist, overlay, tracker};
+use archipelago_rs::{Print, RichText};
+use randomizer_utilities::ui::font_handler;
+use randomizer_utilities::ui::font_handler::{GREEN, WHITE, YELLOW};
+use randomizer_utilities::ui::overlay::D3D11State;
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{LazyLock, Mutex, OnceLock};
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    VK_BACK, VK_ESCAPE, VK_F2, VK_F3, VK_F4, VK_NEXT, VK_PRIOR, VK_RETURN,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    CallWindowProcW, GWLP_WNDPROC, SetWindowLongPtrW, WM_CHAR, WM_KEYDOWN, WM_KEYUP, WNDPROC,
+};
+
+/// Chat messages typed in game, the main thread sends them off as Say packets
+pub static TX_CHAT: OnceLock<Sender<String>> = OnceLock::new();
+
+const MAX_HISTORY: usize = 100;
+const VISIBLE_LINES: usize = 8;
+const MAX_INPUT_LENGTH: usize = 200;
+
+static CHAT_HISTORY: LazyLock<Mutex<VecDeque<Vec<MessageSegment>>>> =
+    LazyLock::new(|| Mutex::new(VecDeque::new()));
+static CHAT_INPUT: Mutex<String> = Mutex::new(String::new());
+/// Whether the input line is open, when it is keyboard input is consumed by the chat
+static CHAT_OPEN: AtomicBool = AtomicBool::new(false);
+/// How many lines up from the newest message the history is scrolled
+static SCROLL_OFFSET: AtomicUsize = AtomicUsize::new(0);
+
+static ORIGINAL_WNDPROC: OnceLock<isize> = OnceLock::new();
+
+pub(crate) fn is_open() -> bool {
+    CHAT_OPEN.load(Ordering::SeqCst)
+}
+
+/// Add a line to the chat history, dropping the oldest one if it's full
+pub(crate) fn add_chat_message(segments: Vec<MessageSegment>) {
+    match CHAT_HISTORY.lock() {
+        Ok(mut history) => {
+            if history.len() >= MAX_HISTORY {
+                history.pop_front();
+            }
+            history.push_back(segments);
+        }
+        Err(err) => {
+            log::error!("PoisonError upon trying to add chat message {:?}", err);
+        }
+    }
+}
+
+/// Split a server print into its parts, players in yellow, locations in green and items colored by
+/// their classification like the received item notifications
+pub(crate) fn get_print_segments(print: &Print) -> Vec<MessageSegment> {
+    print
+        .data()
+        .iter()
+        .map(|part| match part {
+            RichText::Player(player) => MessageSegment::new(player.alias().to_string(), YELLOW),
+            RichText::Item(item) => {
+                MessageSegment::new(item.item().name().to_string(), get_color_for_item(item))
+            }
+            RichText::Location(location) => MessageSegment::new(location.name().to_string(), GREEN),
+            part => MessageSegment::new(part.to_string(), WHITE),
+        })
+        .collect()
+}
+
+/// Subclass the game's window so we can grab keyboard input for the chat
+pub(crate) fn install_keyboard_hook(hwnd: HWND) {
+    if ORIGINAL_WNDPROC.get().is_some() {
+        return;
+    }
+    let original = unsafe { SetWindowLongPtrW(hwnd, GWLP_WNDPROC, chat_wndproc as usize as isize) };
+    if original == 0 {
+        log::error!("Failed to install chat keyboard hook");
+        return;
+    }
+    if ORIGINAL_WNDPROC.set(original).is_err() {
+        log::error!("Chat keyboard hook already set");
+    }
+    log::debug!("Installed chat keyboard hook");
+}
+
+unsafe extern "system" fn chat_wndproc(
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+) -> LRESULT {
+    if handle_key_message(msg, wparam) {
+        return LRESULT(0);
+    }
+    match ORIGINAL_WNDPROC.get() {
+        Some(original) => unsafe {
+            CallWindowProcW(
+                std::mem::transmute::<isize, WNDPROC>(*original),
+                hwnd,
+                msg,
+                wparam,
+                lparam,
+            )
+        },
+        None => LRESULT(0),
+    }
+}
+
+/// Returns true if the message was consumed by the chat
+fn handle_key_message(msg: u32, wparam: WPARAM) -> bool {
+    let open = CHAT_OPEN.load(Ordering::SeqCst);
+    match msg {
+        WM_KEYDOWN => {
+            let key = wparam.0 as u16;
+            if key == VK_F2.0 {
+                CHAT_OPEN.store(!open, Ordering::SeqCst);
+                SCROLL_OFFSET.store(0, Ordering::SeqCst);
+                return true;
+            }
+            if key == VK_F3.0 && !open {
+                tracker::toggle();
+                return true;
+            }
+            if key == VK_F4.0 && !open {
+                checklist::toggle();
+                return true;
+            }
+            if !open {
+                return false;
+            }
+            if key == VK_RETURN.0 {
+                send_input();
+                CHAT_OPEN.store(false, Ordering::SeqCst);
+            } else if key == VK_ESCAPE.0 {
+                CHAT_OPEN.store(false, Ordering::SeqCst);
+            } else if key == VK_BACK.0 {
+                if let Ok(mut input) = CHAT_INPUT.lock() {
+                    input.pop();
+                }
+            } else if key == VK_PRIOR.0 {
+                scroll(1);
+            } else if key == VK_NEXT.0 {
+                scroll(-1);
+            }
+            true
+        }
+        WM_CHAR => {
+            if !open {
+                return false;
+            }
+            if let Some(c) = char::from_u32(wparam.0 as u32)
+                && !c.is_control()
+                && let Ok(mut input) = CHAT_INPUT.lock()
+                && input.chars().count() < MAX_INPUT_LENGTH
+            {
+                input.push(c);
+            }
+            true
+        }
+        WM_KEYUP => open,
+        _ => false,
+    }
+}
+
+fn scroll(lines: isize) {
+    let max = CHAT_HISTORY
+        .lock()
+        .map(|history| history.len().saturating_sub(VISIBLE_LINES))
+        .unwrap_or_default();
+    let current = SCROLL_OFFSET.load(Ordering::SeqCst);
+    SCROLL_OFFSET.store(
+        current.saturating_add_signed(lines).min(max),
+        Ordering::SeqCst,
+    );
+}
+
+/// Chat commands that are handled by the client rather than the server
+const BACKTRACK_COMMAND: &str = "/backtrack";
+
+/// Send whatever was typed to the server, commands such as !hint are handled by the server itself
+fn send_input() {
+    let message = match CHAT_INPUT.lock() {
+        Ok(mut input) => std::mem::take(&mut *input),
+        Err(err) => {
+            log::error!("Failed to get chat input: {}", err);
+            return;
+        }
+    };
+    let message = message.trim();
+    if message.is_empty() {
+        return;
+    }
+    if message == BACKTRACK_COMMAND {
+        let text = match door_randomizer::backtrack_doors() {
+            Ok(room) => format!("Doors in this room now lead back to room {}", room),
+            Err(err) => format!("Unable to backtrack: {}", err),
+        };
+        add_chat_message(vec![MessageSegment::new(text, YELLOW)]);
+        SCROLL_OFFSET.store(0, Ordering::SeqCst);
+        return;
+    }
+    match TX_CHAT.get() {
+        Some(tx) => {
+            if let Err(err) = tx.send(message.to_string()) {
+                log::error!("Failed to send chat message: {}", err);
+            }
+        }
+        None => {
+            add_chat_message(vec![MessageSegment::new(
+                "Not connected, message was not sent".to_string(),
+                YELLOW,
+            )]);
+        }
+    }
+    SCROLL_OFFSET.store(0, Ordering::SeqCst);
+}
+
+pub(crate) fn draw_chat(state: &D3D11State, screen_width: f32, screen_height: f32) {
+    const PADDING: f32 = 12.0;
+    const LINE_HEIGHT: f32 = 24.0;
+    let open = CHAT_OPEN.load(Ordering::SeqCst);
+    let mut y = screen_height - PADDING - LINE_HEIGHT;
+
+    if open {
+        let input = CHAT_INPUT
+            .lock()
+            .map(|input| input.clone())
+            .unwrap_or_default();
+        font_handler::draw_string(
+            state,
+            &format!("> {}_", input),
+            PADDING,
+            y,
+            screen_width,
+            screen_height,
+            &WHITE,
+        );
+        y -= LINE_HEIGHT + PADDING;
+    }
+
+    // History is only shown while chatting
+    if open && let Ok(history) = CHAT_HISTORY.lock() {
+        let offset = SCROLL_OFFSET.load(Ordering::SeqCst);
+        for line in history.iter().rev().skip(offset).take(VISIBLE_LINES) {
+            overlay::draw_segments(state, line, PADDING, y, screen_width, screen_height);
+            y -= LINE_HEIGHT;
+        }
+        if offset > 0 {
+            font_handler::draw_string(
+                state,
+                &format!("({} newer)", offset),
+                PADDING,
+                y,
+                screen_width,
+                screen_height,
+                get_default_color(),
+            );
+        }
+    }
+}
@@ -0,0 +1,136 @@
+use crate::mapping::{ModModeData, get_locations_by_mission};
+use crate::ui::overlay;
+use crate::ui::overlay::{GREY, MessageSegment, get_color_for_item, get_default_color};
+use crate::utilities;
+use archipelago_rs::Client;
+use randomizer_utilities::archipelago_utilities::CACHED_LOCATIONS;
+use randomizer_utilities::ui::font_handler::{GREEN, YELLOW};
+use randomizer_utilities::ui::overlay::D3D11State;
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{LazyLock, RwLock};
+
+/// Whether the checklist is shown, toggled with F4
+static CHECKLIST_OPEN: AtomicBool = AtomicBool::new(false);
+/// The current mission's locations, rebuilt on mission start and whenever a location is sent off
+static CHECKLIST: LazyLock<RwLock<Option<MissionChecklist>>> = LazyLock::new(|| RwLock::new(None));
+
+struct MissionChecklist {
+    mission: u32,
+    entries: Vec<ChecklistEntry>,
+}
+
+struct ChecklistEntry {
+    name: String,
+    checked: bool,
+}
+
+pub(crate) fn toggle() {
+    CHECKLIST_OPEN.fetch_xor(true, Ordering::SeqCst);
+}
+
+/// Rebuild the checklist for the given mission. Includes SS Rank (if enabled) and secret mission
+/// locations, since those are part of the mission's locations
+pub(crate) fn refresh(client: &Client<ModModeData>, mission: u32) {
+    let checked: HashSet<i64> = client.checked_locations().map(|loc| loc.id()).collect();
+    let mut entries: Vec<ChecklistEntry> = get_locations_by_mission(client, mission)
+        .iter()
+        .map(|loc| ChecklistEntry {
+            name: loc.name().to_string(),
+            checked: checked.contains(&loc.id()),
+        })
+        .collect();
+    // Unchecked first, so they're the ones still visible on a long list
+    entries.sort_by(|a, b| a.checked.cmp(&b.checked).then_with(|| a.name.cmp(&b.name)));
+    match CHECKLIST.write() {
+        Ok(mut checklist) => *checklist = Some(MissionChecklist { mission, entries }),
+        Err(err) => log::error!("Failed to update mission checklist: {}", err),
+    }
+}
+
+/// Drop the checklist, for when we disconnect or return to the menu
+pub(crate) fn clear() {
+    if let Ok(mut checklist) = CHECKLIST.write() {
+        *checklist = None;
+    }
+}
+
+fn get_checklist_lines() -> Vec<Vec<MessageSegment>> {
+    let Ok(checklist) = CHECKLIST.read() else {
+        return vec![];
+    };
+    let Some(checklist) = checklist.as_ref() else {
+        return vec![];
+    };
+    let remaining = checklist.entries.iter().filter(|e| !e.checked).count();
+    let mut lines = vec![vec![MessageSegment::new(
+        format!(
+            "Mission {} - {}/{} remaining",
+            checklist.mission,
+            remaining,
+            checklist.entries.len()
+        ),
+        YELLOW,
+    )]];
+    // Scouted contents are looked up when drawing, scouts may still be coming in after the refresh
+    let cached = CACHED_LOCATIONS.read().ok();
+    for entry in checklist.entries.iter() {
+        let mut line = vec![MessageSegment::new(
+            format!("[{}] {}", if entry.checked { "X" } else { " " }, entry.name),
+            if entry.checked {
+                GREY
+            } else {
+                *get_default_color()
+            },
+        )];
+        if let Some(item) = cached
+            .as_ref()
+            .and_then(|cached| cached.get(entry.name.as_str()))
+        {
+            line.push(MessageSegment::new(" - ".to_string(), GREY));
+            let item_name = if item.sender() == item.receiver() {
+                item.item().name().to_string()
+            } else {
+                format!("{}'s {}", item.receiver().alias(), item.item().name())
+            };
+            line.push(MessageSegment::new(
+                item_name,
+                if entry.checked {
+                    GREEN
+                } else {
+                    get_color_for_item(item)
+                },
+            ));
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+pub(crate) fn draw_checklist(state: &D3D11State, screen_width: f32, screen_height: f32) {
+    const PADDING: f32 = 12.0;
+    const LINE_HEIGHT: f32 = 24.0;
+    if !CHECKLIST_OPEN.load(Ordering::SeqCst) || utilities::is_on_main_menu() {
+        return;
+    }
+    let mut lines = get_checklist_lines();
+    // Only as many lines as fit on screen, the header and unchecked entries come first so the tail is cut
+    let max_lines = ((screen_height - PADDING * 2.0) / LINE_HEIGHT)
+        .floor()
+        .max(2.0) as usize;
+    if lines.len() > max_lines {
+        let hidden = lines.len() - (max_lines - 1);
+        lines.truncate(max_lines - 1);
+        lines.push(vec![MessageSegment::new(
+            format!("...and {} more", hidden),
+            GREY,
+        )]);
+    }
+    // Anchored to the bottom right, out of the way of the tracker and the notification stack
+    let top = screen_height - PADDING - lines.len() as f32 * LINE_HEIGHT;
+    for (i, line) in lines.iter().enumerate() {
+        let x = screen_width - PADDING - overlay::segments_width(state, line);
+        let y = top + i as f32 * LINE_HEIGHT;
+        overlay::draw_segments(state, line, x, y, screen_width, screen_height);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::ui::chat;
 use crate::ui::overlay::{present_hook, resize_hook};
 use crate::utilities::DMC3_ADDRESS;
 use randomizer_utilities::ui::dx11::{
@@ -45,6 +46,10 @@ unsafe extern "system" fn hook_d3d11_create_device_and_swap_chain(
             ppimmediatecontext,
         )
     };
+    if !pswapchaindesc.is_null() {
+        chat::install_keyboard_hook(unsafe { (*pswapchaindesc).OutputWindow });
+    }
+
     match install_vtable_hook(ppswapchain, 8, present_hook as PresentFn, &ORIGINAL_PRESENT) {
         Ok(_) => {
             log::debug!("Installed present hook");
@@ -1,3 +1,7 @@
+pub(crate) mod chat;
+pub(crate) mod checklist;
 pub(crate) mod dx11_hooks;
 pub(crate) mod overlay;
 pub mod text_handler;
+pub(crate) mod text_markup;
+pub(crate) mod tracker;
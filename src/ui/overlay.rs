@@ -1,15 +1,21 @@
-use crate::archipelago::CONNECTED;
+use crate::archipelago::{CONNECTED, LAST_SYNC};
+use crate::mapping::ModModeData;
+use crate::ui::{chat, checklist, tracker};
 use crate::utilities::is_crimson_loaded;
-use crate::{mapping, utilities};
-use archipelago_rs::LocatedItem;
+use crate::{config, goal, mapping, utilities};
+use archipelago_rs::{Client, LocatedItem};
 use randomizer_utilities::dmc::loader_parser::LOADER_STATUS;
+use randomizer_utilities::item_sync;
 use randomizer_utilities::ui::dx11::{ORIGINAL_PRESENT, ORIGINAL_RESIZE_BUFFERS};
-use randomizer_utilities::ui::font_handler::{BLACK, FontAtlas, FontColorCB, GREEN, RED, WHITE};
+use randomizer_utilities::ui::font_handler::{
+    BLACK, FontAtlas, FontColorCB, GREEN, RED, WHITE, YELLOW,
+};
 use randomizer_utilities::ui::overlay::{D3D11State, STATE};
 use randomizer_utilities::ui::{font_handler, overlay};
+use serde::{Deserialize, Serialize};
 use std::collections::VecDeque;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::{LazyLock, Mutex, RwLockReadGuard};
+use std::sync::{LazyLock, Mutex, RwLock, RwLockReadGuard};
 use std::time::{Duration, Instant};
 use windows::Win32::Graphics::Direct3D11::ID3D11Texture2D;
 use windows::Win32::Graphics::Direct3D11::*;
@@ -37,7 +43,7 @@ pub struct OverlayMessage {
     duration: Duration,
     _x: f32,
     _y: f32,
-    _msg_type: MessageType,
+    msg_type: MessageType,
 }
 
 impl OverlayMessage {
@@ -53,14 +59,20 @@ impl OverlayMessage {
             duration,
             _x: x,
             _y: y,
-            _msg_type: msg_type,
+            msg_type,
         }
     }
 }
-// TODO This doesn't matter right now, but it could be used later
+// TODO Coordinates don't matter right now, but they could be used later
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub(crate) enum MessageType {
-    _Default,     // Take the X and Y values as they are given
-    Notification, // Disregard coordinates, automatically align to upper right (Used for newly received items+DL)
+    #[serde(rename = "Default")]
+    _Default, // Take the X and Y values as they are given
+    // The rest disregard coordinates and go in the notification stack, they're split up so the duration can be configured
+    Notification,
+    ItemReceived,
+    ItemSent,
+    DeathLink,
 }
 
 pub fn get_default_color() -> &'static FontColorCB {
@@ -110,6 +122,10 @@ pub(crate) unsafe extern "system" fn resize_hook(
 }
 
 pub(crate) static CANT_PURCHASE: AtomicBool = AtomicBool::new(false);
+/// Requirements of the adjudicator in the current room, shown before the fight
+pub(crate) static ADJUDICATOR_PREVIEW: RwLock<Option<String>> = RwLock::new(None);
+/// Actual mission behind the highlighted select screen slot, refreshed every frame the screen is up
+pub(crate) static MISSION_SELECT_PREVIEW: RwLock<Option<(String, Instant)>> = RwLock::new(None);
 
 unsafe fn update_screen_size(swap_chain: &IDXGISwapChain) -> (f32, f32) {
     let back_buffer: ID3D11Texture2D = {
@@ -149,7 +165,7 @@ pub(crate) unsafe extern "system" fn present_hook(
                 }]));
             }
 
-            if (utilities::is_on_main_menu() || should_display_anyway())
+            if utilities::is_on_main_menu()
                 && let Some(atlas) = &state.atlas
             {
                 const STATUS: &str = "Status: ";
@@ -178,6 +194,45 @@ pub(crate) unsafe extern "system" fn present_hook(
                 );
                 draw_version_info(&state, screen_width, screen_height, atlas);
             }
+            if !utilities::is_on_main_menu() {
+                draw_status_banner(&state, screen_width, screen_height);
+            }
+            if (utilities::is_on_main_menu() || chat::is_open() || tracker::is_open())
+                && let Some(atlas) = &state.atlas
+            {
+                draw_goal_progress(&state, screen_width, screen_height, atlas);
+            }
+            if let Ok(preview) = ADJUDICATOR_PREVIEW.read()
+                && let Some(preview) = preview.as_ref()
+                && let Some(atlas) = &state.atlas
+            {
+                let width = preview.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>();
+                font_handler::draw_string(
+                    &state,
+                    preview,
+                    (screen_width - width) / 2.0,
+                    screen_height * 0.75,
+                    screen_width,
+                    screen_height,
+                    &YELLOW,
+                );
+            }
+            if let Ok(preview) = MISSION_SELECT_PREVIEW.read()
+                && let Some((preview, updated)) = preview.as_ref()
+                && updated.elapsed() < Duration::from_millis(250)
+                && let Some(atlas) = &state.atlas
+            {
+                let width = preview.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>();
+                font_handler::draw_string(
+                    &state,
+                    preview,
+                    (screen_width - width) / 2.0,
+                    screen_height * 0.15,
+                    screen_width,
+                    screen_height,
+                    &YELLOW,
+                );
+            }
             if CANT_PURCHASE.load(Ordering::SeqCst)
                 && let Some(atlas) = &state.atlas
             {
@@ -225,12 +280,25 @@ pub(crate) unsafe extern "system" fn present_hook(
                 const PADDING: f32 = 12.0;
                 const LINE_HEIGHT: f32 = 24.0;
 
-                let mut y = PADDING;
+                let settings = &config::CONFIG.overlay;
+                let padding = PADDING * settings.spacing_scale;
+                let line_height = LINE_HEIGHT * get_text_scale();
+                let step = line_height + padding;
+                // Newest message is closest to the anchored corner
+                let (mut y, step) = if settings.anchor.is_bottom() {
+                    (screen_height - padding - line_height, -step)
+                } else {
+                    (padding, step)
+                };
                 for msg in active.iter().rev() {
                     draw_colored_message(&state, msg, screen_width, screen_height, y);
-                    y += LINE_HEIGHT + PADDING;
+                    y += step;
                 }
             }
+
+            tracker::draw_tracker(&state, screen_width, screen_height);
+            checklist::draw_checklist(&state, screen_width, screen_height);
+            chat::draw_chat(&state, screen_width, screen_height);
         }
         Err(err) => {
             log::error!("Failed to get resources: {:?}", err);
@@ -253,12 +321,15 @@ fn draw_version_info(
     const GAME_VERSION: &str = "Game Version:";
     const ADDITIONAL_MODS: &str = "Additional Mods:";
     // TODO Maybe at some point I'd want to have the mod poke github on launch?
+    const GLYPH_HEIGHT: f32 = 24.0;
+    const GAP: f32 = 26.0;
+    let line_height = GLYPH_HEIGHT + GAP * config::CONFIG.overlay.spacing_scale;
     font_handler::draw_string(
         state,
         &format!("{} {}", MOD_VERSION, env!("CARGO_PKG_VERSION")),
         0.0,
         //VERSION.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>(),
-        100.0,
+        line_height * 2.0,
         screen_width,
         screen_height,
         get_default_color(),
@@ -272,7 +343,7 @@ fn draw_version_info(
             &format!("{} {}", MODE, mapping.mode),
             0.0,
             //VERSION.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>(),
-            50.0,
+            line_height,
             screen_width,
             screen_height,
             get_default_color(),
@@ -283,7 +354,7 @@ fn draw_version_info(
                 &format!("{} {}", AP_VERSION, cv),
                 0.0,
                 //VERSION.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>(),
-                150.0,
+                line_height * 3.0,
                 screen_width,
                 screen_height,
                 get_default_color(),
@@ -295,7 +366,7 @@ fn draw_version_info(
                 &format!("{} {}", ROOM_VERSION, gv),
                 0.0,
                 //VERSION.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>(),
-                200.0,
+                line_height * 4.0,
                 screen_width,
                 screen_height,
                 get_default_color(),
@@ -307,7 +378,7 @@ fn draw_version_info(
             state,
             GAME_VERSION,
             0.0,
-            250.0,
+            line_height * 5.0,
             screen_width,
             screen_height,
             get_default_color(),
@@ -319,7 +390,7 @@ fn draw_version_info(
                 .chars()
                 .map(|c| atlas.glyph_advance(c))
                 .sum::<f32>(),
-            250.0,
+            line_height * 5.0,
             screen_width,
             screen_height,
             if status.game_information.valid_for_use {
@@ -332,18 +403,17 @@ fn draw_version_info(
             state,
             ADDITIONAL_MODS,
             0.0,
-            300.0,
+            line_height * 6.0,
             screen_width,
             screen_height,
             get_default_color(),
         );
         for (i, mod_info) in status.mod_information.iter().enumerate() {
-            let base = 350;
             font_handler::draw_string(
                 state,
                 mod_info.description,
                 0.0,
-                (base + (i * 50)) as f32,
+                line_height * (7 + i) as f32,
                 screen_width,
                 screen_height,
                 if mod_info.valid_for_use { &GREEN } else { &RED },
@@ -352,41 +422,176 @@ fn draw_version_info(
     }
 }
 
-fn should_display_anyway() -> bool {
-    // TODO Use this to display if we are connected, then disconnected
-    // Or if version mismatch?
-
-    false
-}
-
-fn draw_colored_message(
-    state: &D3D11State,
-    msg: &TimedMessage,
+fn draw_goal_progress(
+    state: &RwLockReadGuard<D3D11State>,
     screen_width: f32,
     screen_height: f32,
-    y: f32,
+    atlas: &FontAtlas,
 ) {
+    const PADDING: f32 = 12.0;
+    const LINE_HEIGHT: f32 = 24.0;
+    if !CONNECTED.load(Ordering::SeqCst) {
+        return;
+    }
+    let Ok(status) = goal::GOAL_STATUS.read() else {
+        return;
+    };
+    let Some(status) = status.as_ref() else {
+        return;
+    };
+    let mut lines = vec![(format!("Goal: {}", status.goal), get_default_color())];
+    if let Some(conditions) = &status.conditions {
+        lines.push((format!("Conditions: {}", conditions), get_default_color()));
+    }
+    lines.push((
+        format!(
+            "Missions: {}/{}",
+            status.missions_completed, status.missions_required
+        ),
+        get_default_color(),
+    ));
+    if let Some(next) = status.next_mission {
+        lines.push((format!("Next Mission: {}", next), &YELLOW));
+    }
+    if status.bp_floor > 0 {
+        lines.push((
+            format!("BP Floor: {}", status.bp_floor),
+            get_default_color(),
+        ));
+    }
+    if status.goal_reached {
+        lines.push(("Goal Complete!".to_string(), &GREEN));
+    }
+    // Centered under the status banner, clear of the notification corners, the tracker, the checklist and chat
+    let settings = &config::CONFIG.overlay;
+    let padding = PADDING * settings.spacing_scale;
+    let line_height = LINE_HEIGHT + padding;
+    let top = padding + line_height;
+    for (i, (text, color)) in lines.iter().enumerate() {
+        let width = text.chars().map(|c| atlas.glyph_advance(c)).sum::<f32>();
+        font_handler::draw_string(
+            state,
+            text,
+            (screen_width - width) / 2.0,
+            top + (i as f32 * line_height),
+            screen_width,
+            screen_height,
+            color,
+        );
+    }
+}
+
+/// Problems worth knowing about mid-mission. Empty when everything is fine, so the banner clears
+/// itself once things recover
+fn get_status_problems() -> Vec<MessageSegment> {
+    let mut problems = vec![];
+    let last_sync = LAST_SYNC.read().ok().and_then(|last| *last);
+    if !CONNECTED.load(Ordering::SeqCst)
+        && let Some(last_sync) = last_sync
+    {
+        problems.push(MessageSegment::new(
+            format!(
+                "Disconnected, last synced {} ago",
+                format_elapsed(last_sync.elapsed())
+            ),
+            RED,
+        ));
+    }
+    let offline_checks = item_sync::OFFLINE_CHECKS
+        .lock()
+        .map(|checks| checks.len())
+        .unwrap_or_default();
+    if offline_checks > 0 {
+        problems.push(MessageSegment::new(
+            format!(
+                "{} offline check{} queued",
+                offline_checks,
+                if offline_checks == 1 { "" } else { "s" }
+            ),
+            YELLOW,
+        ));
+    }
+    if let Ok(info) = mapping::OVERLAY_INFO.read()
+        && let (Some(cv), Some(gv)) = (&info.client_version, &info.generated_version)
+        && cv.to_string() != gv.to_string()
+    {
+        problems.push(MessageSegment::new(
+            format!("Version mismatch: client {}, room {}", cv, gv),
+            YELLOW,
+        ));
+    }
+    if let Some(status) = LOADER_STATUS.get()
+        && !status.game_information.valid_for_use
+    {
+        problems.push(MessageSegment::new(
+            format!(
+                "Invalid game version: {}",
+                status.game_information.description
+            ),
+            RED,
+        ));
+    }
+    problems
+}
+
+fn format_elapsed(elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    match secs {
+        0..60 => format!("{}s", secs),
+        60..3600 => format!("{}m {}s", secs / 60, secs % 60),
+        _ => format!("{}h {}m", secs / 3600, (secs % 3600) / 60),
+    }
+}
+
+/// Compact banner along the top middle of the screen, the main menu already shows all of this
+fn draw_status_banner(state: &D3D11State, screen_width: f32, screen_height: f32) {
+    const PADDING: f32 = 12.0;
+    const SEPARATOR: &str = " | ";
+    let problems = get_status_problems();
+    if problems.is_empty() {
+        return;
+    }
+    let mut segments = vec![];
+    for (i, problem) in problems.into_iter().enumerate() {
+        if i > 0 {
+            segments.push(MessageSegment::new(SEPARATOR.to_string(), WHITE));
+        }
+        segments.push(problem);
+    }
+    let x = (screen_width - segments_width(state, &segments)) / 2.0;
+    draw_segments(state, &segments, x, PADDING, screen_width, screen_height);
+}
+
+/// Used for things that are done with, or not found yet
+pub(crate) const GREY: FontColorCB = FontColorCB::new(0.5, 0.5, 0.5, 1.0);
+
+/// Width of text as it's drawn, estimated if the font atlas isn't loaded
+pub(crate) fn text_width(state: &D3D11State, text: &str) -> f32 {
     const FALLBACK_MULT: f32 = 32.0;
-    let total_width: f32 = msg
-        .message
-        .segments
-        .iter()
-        .map(|seg| {
-            if let Some(atlas) = &state.atlas {
-                seg.text
-                    .chars()
-                    .map(|c| atlas.glyph_advance(c))
-                    .sum::<f32>()
-            } else {
-                seg.text.len() as f32 * FALLBACK_MULT
-            }
-        })
-        .sum();
+    match &state.atlas {
+        Some(atlas) => text.chars().map(|c| atlas.glyph_advance(c)).sum(),
+        None => text.len() as f32 * FALLBACK_MULT,
+    }
+}
 
-    // Start on right
-    let mut cursor_x = screen_width - total_width;
+pub(crate) fn segments_width(state: &D3D11State, segments: &[MessageSegment]) -> f32 {
+    segments
+        .iter()
+        .map(|segment| text_width(state, &segment.text))
+        .sum()
+}
 
-    for segment in msg.message.segments.iter() {
+/// Draw segments one after another starting at x, returns the x the line ends at
+pub(crate) fn draw_segments(
+    state: &D3D11State,
+    segments: &[MessageSegment],
+    x: f32,
+    y: f32,
+    screen_width: f32,
+    screen_height: f32,
+) -> f32 {
+    let mut cursor_x = x;
+    for segment in segments {
         font_handler::draw_string(
             state,
             &segment.text,
@@ -396,17 +601,39 @@ fn draw_colored_message(
             screen_height,
             &segment.color,
         );
-
-        if let Some(atlas) = &state.atlas {
-            cursor_x += segment
-                .text
-                .chars()
-                .map(|c| atlas.glyph_advance(c))
-                .sum::<f32>();
-        } else {
-            cursor_x += segment.text.len() as f32 * FALLBACK_MULT;
-        }
+        cursor_x += text_width(state, &segment.text);
     }
+    cursor_x
+}
+
+fn draw_colored_message(
+    state: &D3D11State,
+    msg: &TimedMessage,
+    screen_width: f32,
+    screen_height: f32,
+    y: f32,
+) {
+    let scale = get_text_scale();
+    let x = if config::CONFIG.overlay.anchor.is_left() {
+        0.0
+    } else {
+        screen_width - segments_width(state, &msg.message.segments) * scale
+    };
+    // Glyphs are drawn at the atlas' pixel size relative to the given screen size, so shrinking the screen we hand
+    // over scales the text up by the same amount
+    draw_segments(
+        state,
+        &msg.message.segments,
+        x / scale,
+        y / scale,
+        screen_width / scale,
+        screen_height / scale,
+    );
+}
+
+fn get_text_scale() -> f32 {
+    const MIN_SCALE: f32 = 0.25;
+    config::CONFIG.overlay.text_scale.max(MIN_SCALE)
 }
 
 struct TimedMessage {
@@ -415,10 +642,22 @@ struct TimedMessage {
 }
 
 fn pop_buffer_message() {
+    if ACTIVE_MESSAGES
+        .lock()
+        .is_ok_and(|active| active.len() >= config::CONFIG.overlay.max_visible_lines.max(1))
+    {
+        return;
+    }
     if let Ok(mut queue) = MESSAGE_QUEUE.lock()
         && let Some(message) = queue.pop_front()
     {
-        let expiration = Instant::now() + message.duration;
+        let duration = config::CONFIG
+            .overlay
+            .durations
+            .get(&message.msg_type)
+            .and_then(|secs| Duration::try_from_secs_f32(*secs).ok())
+            .unwrap_or(message.duration);
+        let expiration = Instant::now() + duration;
         let timed = TimedMessage {
             message,
             expiration,
@@ -429,6 +668,16 @@ fn pop_buffer_message() {
     }
 }
 
+/// Whether an item should get a notification, based off the overlay filters in the config
+pub(crate) fn should_notify_item(client: &Client<ModModeData>, item: &LocatedItem) -> bool {
+    let settings = &config::CONFIG.overlay;
+    let filler = !item.is_trap() && !item.is_useful() && !item.is_progression();
+    !(settings.only_for_me && item.receiver().slot() != client.this_player().slot()
+        || settings.only_progression && !item.is_progression()
+        || settings.hide_filler && filler
+        || settings.suppress_red_orb_filler && item.item().name().starts_with("Red Orb"))
+}
+
 pub(crate) fn get_color_for_item(item: &LocatedItem) -> FontColorCB {
     const CYAN: FontColorCB = FontColorCB::new(0.0, 0.933, 0.933, 1.0);
     const PLUM: FontColorCB = FontColorCB::new(0.686, 0.6, 0.937, 1.0);
@@ -1,18 +1,76 @@
-use crate::create_hook;
+use crate::constants::REMOTE_ID;
+use crate::ui::text_markup;
+use crate::ui::text_markup::MessageBuilder;
 use crate::utilities::DMC3_ADDRESS;
+use crate::{config, create_hook};
+use archipelago_rs::LocatedItem;
 use minhook::{MH_STATUS, MinHook};
-use randomizer_utilities::replace_single_byte;
+use randomizer_utilities::{read_data_from_address, replace_single_byte};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::ptr;
 use std::ptr::write_unaligned;
 use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
-use std::sync::{LazyLock, OnceLock};
+use std::sync::{LazyLock, OnceLock, RwLock};
+
+/// Items that the game has no graphic of its own for
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub(crate) enum DisplayItem {
+    Style,
+    RemoteProgression,
+    RemoteUseful,
+    RemoteFiller,
+    RemoteTrap,
+}
+
+/// In-game item graphics shown for each DisplayItem on the item get screen, registered from the config.
+/// Anything unregistered is drawn as Remote until there are proper custom icons, other in-game graphics
+/// would look like a key item was found. Only drawn, the item actually given is still Remote
+static ITEM_GRAPHICS: LazyLock<RwLock<HashMap<DisplayItem, u32>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// Swap out the graphic used for a DisplayItem
+pub(crate) fn register_item_graphic(item: DisplayItem, graphic_id: u32) {
+    match ITEM_GRAPHICS.write() {
+        Ok(mut graphics) => {
+            graphics.insert(item, graphic_id);
+        }
+        Err(err) => log::error!("Failed to register item graphic: {}", err),
+    }
+}
+
+pub(crate) fn get_item_graphic(item: DisplayItem) -> u32 {
+    ITEM_GRAPHICS
+        .read()
+        .ok()
+        .and_then(|graphics| graphics.get(&item).copied())
+        .unwrap_or(*REMOTE_ID)
+}
+
+/// Graphic for an item belonging to another world, based on its classification
+pub(crate) fn get_remote_item_graphic(item: &LocatedItem) -> u32 {
+    get_item_graphic(
+        match (item.is_trap(), item.is_useful(), item.is_progression()) {
+            (true, _, _) => DisplayItem::RemoteTrap,
+            (false, _, true) => DisplayItem::RemoteProgression,
+            (false, true, false) => DisplayItem::RemoteUseful,
+            (false, false, false) => DisplayItem::RemoteFiller,
+        },
+    )
+}
 
 pub static CANCEL_TEXT: AtomicBool = AtomicBool::new(false);
 pub static LAST_OBTAINED_ID: AtomicU8 = AtomicU8::new(0);
+/// Graphic drawn in place of LAST_OBTAINED_ID on the item get screen, 0 if there isn't one
+pub static LAST_OBTAINED_GRAPHIC: AtomicU8 = AtomicU8::new(0);
+const ITEM_GET_ID_OFFSET: usize = 0x36;
 static TEXT_DISPLAYED: LazyLock<usize> = LazyLock::new(|| *DMC3_ADDRESS + 0xCB89A0); // 0x01 if text is being displayed
 
 pub unsafe fn setup_text_hooks() -> Result<(), MH_STATUS> {
     log::debug!("Setting up text related hooks");
+    for (item, graphic_id) in config::CONFIG.item_graphics.iter() {
+        register_item_graphic(*item, *graphic_id);
+    }
     unsafe {
         create_hook!(
             DISPLAY_ITEM_GET_ADDR,
@@ -55,17 +113,38 @@ pub fn display_message_via_index(message: &str) {
 }
 
 pub fn replace_unused_with_text(message: String) {
+    replace_unused_with_markup(&MessageBuilder::default().text(&message).build());
+}
+
+/// Write an already built message (See MessageBuilder) into the unused message slot
+pub fn replace_unused_with_markup(markup: &str) {
+    const MESSAGE_TABLE: usize = 0xCB9340;
     let base = *DMC3_ADDRESS;
     unsafe {
-        let message_begin: usize = GET_MESSAGE_START.get_or_init(|| {
+        let get_message_start = GET_MESSAGE_START.get_or_init(|| {
             std::mem::transmute(base + 0x2F1180) // Offset to function
-        })(base + 0xCB9340, UNUSED_INDEX);
-        let message = message.replace('\n', "<BR>");
-        let msg = format!("<PS 85 305><SZ 24><IT 0>{message}<NE>\x00");
-        let bytes = msg.as_bytes();
-        if message_begin != 0 {
-            ptr::copy_nonoverlapping(bytes.as_ptr(), message_begin as *mut u8, bytes.len());
+        });
+        let message_begin = get_message_start(base + MESSAGE_TABLE, UNUSED_INDEX);
+        // Whatever fits before the next message starts is ours
+        let next_begin = get_message_start(base + MESSAGE_TABLE, UNUSED_INDEX + 1);
+        if message_begin == 0 || next_begin <= message_begin {
+            log::error!(
+                "Couldn't find the room for message {}, not replacing it",
+                UNUSED_INDEX
+            );
+            return;
+        }
+        let max_length = next_begin - message_begin;
+        if markup.len() > max_length {
+            log::warn!(
+                "Message is {} bytes, cutting it down to {}",
+                markup.len(),
+                max_length
+            );
         }
+        let markup = text_markup::truncate(markup, max_length);
+        let bytes = markup.as_bytes();
+        ptr::copy_nonoverlapping(bytes.as_ptr(), message_begin as *mut u8, bytes.len());
     }
 }
 
@@ -77,19 +156,35 @@ pub fn replace_displayed_item_id(item_get: usize) {
         randomizer_utilities::modify_protected_memory(
             || unsafe {
                 write_unaligned(offset, [0xBA, 60u8, 0x00, 0x00, 0x00, 0x90]);
-                if let Some(original) = DISPLAY_ITEM_GET_SCREEN.get() {
-                    original(item_get);
-                }
+                draw_with_graphic(item_get);
                 write_unaligned(offset, [0x8B, 0x93, 0x44, 0x09, 0x00, 0x00]);
             },
             offset,
         )
         .unwrap();
     } else {
-        unsafe {
-            if let Some(original) = DISPLAY_ITEM_GET_SCREEN.get() {
-                original(item_get);
-            }
+        draw_with_graphic(item_get);
+    }
+}
+
+/// Set the graphic to draw for the next item get screen, None to draw the given item as is
+pub(crate) fn set_display_graphic(graphic_id: Option<u32>) {
+    LAST_OBTAINED_GRAPHIC.store(graphic_id.unwrap_or(0) as u8, Ordering::SeqCst);
+}
+
+/// Draw the item get screen with LAST_OBTAINED_GRAPHIC swapped in, putting the given item back afterward
+fn draw_with_graphic(item_get: usize) {
+    let graphic_id = LAST_OBTAINED_GRAPHIC.load(Ordering::SeqCst);
+    let given_id = read_data_from_address::<u8>(item_get + ITEM_GET_ID_OFFSET);
+    unsafe {
+        if graphic_id != 0 {
+            replace_single_byte(item_get + ITEM_GET_ID_OFFSET, graphic_id);
+        }
+        if let Some(original) = DISPLAY_ITEM_GET_SCREEN.get() {
+            original(item_get);
+        }
+        if graphic_id != 0 {
+            replace_single_byte(item_get + ITEM_GET_ID_OFFSET, given_id);
         }
     }
 }
@@ -100,7 +195,10 @@ pub static SETUP_ITEM_GET_SCREEN: OnceLock<unsafe extern "C" fn(ptr: usize)> = O
 pub fn setup_item_get_screen(item_get: usize) {
     unsafe {
         if LAST_OBTAINED_ID.load(Ordering::SeqCst) != 0 {
-            replace_single_byte(item_get + 0x36, LAST_OBTAINED_ID.load(Ordering::SeqCst));
+            replace_single_byte(
+                item_get + ITEM_GET_ID_OFFSET,
+                LAST_OBTAINED_ID.load(Ordering::SeqCst),
+            );
         }
         if let Some(original) = SETUP_ITEM_GET_SCREEN.get() {
             original(item_get);
@@ -116,6 +214,8 @@ pub fn destroy_item_get_screen(item_get: usize, _param_1: u32) {
     if CANCEL_TEXT.load(Ordering::SeqCst) {
         CANCEL_TEXT.store(false, Ordering::SeqCst);
     }
+    // Don't carry the graphic over to the next pickup, that might be a vanilla one
+    set_display_graphic(None);
     unsafe {
         if let Some(original) = DISPLAY_ITEM_GET_SCREEN.get() {
             original(item_get);
@@ -0,0 +1,278 @@
+use archipelago_rs::LocatedItem;
+
+/// Characters that fit on one line of the item get dialog at the default text size
+const LINE_WIDTH: usize = 40;
+const DEFAULT_SIZE: u8 = 24;
+const DEFAULT_POSITION: (u16, u16) = (85, 305);
+const TERMINATOR: &str = "<NE>\x00";
+
+/// Builds text in DMC3's message format. Text is escaped and word wrapped to the dialog's width as it's added.
+/// The game's messages don't appear to support colors, so styling is limited to size
+pub(crate) struct MessageBuilder {
+    markup: String,
+    size: u8,
+    line_length: usize,
+}
+
+impl Default for MessageBuilder {
+    fn default() -> Self {
+        MessageBuilder::new(DEFAULT_POSITION.0, DEFAULT_POSITION.1)
+    }
+}
+
+impl MessageBuilder {
+    pub(crate) fn new(x: u16, y: u16) -> Self {
+        MessageBuilder {
+            markup: format!("<PS {} {}><SZ {}><IT 0>", x, y, DEFAULT_SIZE),
+            size: DEFAULT_SIZE,
+            line_length: 0,
+        }
+    }
+
+    /// Change the size of the text that comes after
+    pub(crate) fn size(mut self, size: u8) -> Self {
+        if size != self.size && size > 0 {
+            // Keep the line length relative to the new size so wrapping stays accurate
+            self.line_length = self.line_length * self.size as usize / size as usize;
+            self.size = size;
+            self.markup.push_str(&format!("<SZ {}>", size));
+        }
+        self
+    }
+
+    pub(crate) fn line_break(mut self) -> Self {
+        self.markup.push_str("<BR>");
+        self.line_length = 0;
+        self
+    }
+
+    /// Add text, newlines become line breaks
+    pub(crate) fn text(mut self, text: &str) -> Self {
+        for (i, line) in text.split('\n').enumerate() {
+            if i > 0 {
+                self = self.line_break();
+            }
+            self.push_words(line);
+        }
+        self
+    }
+
+    fn push_words(&mut self, line: &str) {
+        let max = self.max_line_length();
+        // Keep leading whitespace so segments can be joined with spaces
+        let mut pending_space = line.starts_with(char::is_whitespace);
+        for word in line.split_whitespace() {
+            let word = escape(word);
+            let mut word = word.as_str();
+            let space = usize::from(pending_space && self.line_length > 0);
+            if self.line_length > 0 && self.line_length + space + word.len() > max {
+                self.markup.push_str("<BR>");
+                self.line_length = 0;
+            } else if space == 1 {
+                self.markup.push(' ');
+                self.line_length += 1;
+            }
+            // Words that can't fit on a line by themselves get split
+            while word.len() > max.saturating_sub(self.line_length) {
+                let (start, rest) = word.split_at(max.saturating_sub(self.line_length).max(1));
+                self.markup.push_str(start);
+                self.markup.push_str("<BR>");
+                self.line_length = 0;
+                word = rest;
+            }
+            self.markup.push_str(word);
+            self.line_length += word.len();
+            pending_space = true;
+        }
+        if line.ends_with(char::is_whitespace) && self.line_length > 0 && self.line_length < max {
+            self.markup.push(' ');
+            self.line_length += 1;
+        }
+    }
+
+    fn max_line_length(&self) -> usize {
+        (LINE_WIDTH * DEFAULT_SIZE as usize / self.size as usize).max(1)
+    }
+
+    /// Finish off the message, including the terminator
+    pub(crate) fn build(mut self) -> String {
+        self.markup.push_str(TERMINATOR);
+        self.markup
+    }
+}
+
+/// Cut a built message down to at most max bytes, without splitting a tag and keeping the terminator
+pub(crate) fn truncate(markup: &str, max: usize) -> String {
+    if markup.len() <= max {
+        return markup.to_string();
+    }
+    let body = markup.strip_suffix(TERMINATOR).unwrap_or(markup);
+    let mut end = max.saturating_sub(TERMINATOR.len()).min(body.len());
+    while !body.is_char_boundary(end) {
+        end -= 1;
+    }
+    let mut body = &body[..end];
+    if let Some(open) = body.rfind('<')
+        && !body[open..].contains('>')
+    {
+        body = &body[..open];
+    }
+    format!("{}{}", body, TERMINATOR)
+}
+
+/// Only plain ASCII is displayable, and angle brackets would start a tag
+pub(crate) fn escape(text: &str) -> String {
+    text.chars()
+        .map(|c| match c {
+            '<' => '[',
+            '>' => ']',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => '?',
+        })
+        .collect()
+}
+
+fn get_classification(item: &LocatedItem) -> &'static str {
+    match (item.is_trap(), item.is_useful(), item.is_progression()) {
+        (true, _, _) => "trap",
+        (false, _, true) => "progression",
+        (false, true, false) => "useful",
+        (false, false, false) => "filler",
+    }
+}
+
+/// "Found X's Y (classification)", or just "Found Y" for our own items
+pub(crate) fn item_found_message(item: &LocatedItem) -> String {
+    let found = if item.sender() == item.receiver() {
+        "Found ".to_string()
+    } else {
+        format!("Found {}'s ", item.receiver().alias())
+    };
+    MessageBuilder::default()
+        .text(&found)
+        .text(&item.item().name())
+        .size(DEFAULT_SIZE - 4)
+        .text(&format!(" ({})", get_classification(item)))
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "<PS 85 305><SZ 24><IT 0>";
+
+    /// The text of each line, without the header, size tags or terminator
+    fn lines(markup: &str) -> Vec<String> {
+        let body = markup
+            .strip_prefix(HEADER)
+            .and_then(|body| body.strip_suffix(TERMINATOR))
+            .unwrap();
+        body.split("<BR>")
+            .map(|line| {
+                let mut text = String::new();
+                let mut in_tag = false;
+                for c in line.chars() {
+                    match c {
+                        '<' => in_tag = true,
+                        '>' => in_tag = false,
+                        c if !in_tag => text.push(c),
+                        _ => {}
+                    }
+                }
+                text
+            })
+            .collect()
+    }
+
+    #[test]
+    fn build_adds_terminator() {
+        assert_eq!(
+            MessageBuilder::default().build(),
+            format!("{}<NE>\x00", HEADER)
+        );
+        assert_eq!(
+            MessageBuilder::new(0, 0).text("Hello world").build(),
+            "<PS 0 0><SZ 24><IT 0>Hello world<NE>\x00"
+        );
+    }
+
+    #[test]
+    fn wraps_at_line_width() {
+        let message = MessageBuilder::default().text(&"word ".repeat(20)).build();
+        let lines = lines(&message);
+        assert_eq!(lines.len(), 3);
+        assert!(lines.iter().all(|line| line.len() <= LINE_WIDTH));
+        assert_eq!(lines[0].trim_end(), "word ".repeat(8).trim_end());
+    }
+
+    #[test]
+    fn newlines_become_line_breaks() {
+        let message = MessageBuilder::default().text("one\ntwo").build();
+        assert_eq!(lines(&message), vec!["one", "two"]);
+    }
+
+    #[test]
+    fn splits_over_long_words() {
+        let message = MessageBuilder::default().text(&"a".repeat(100)).build();
+        let lengths: Vec<usize> = lines(&message).iter().map(String::len).collect();
+        assert_eq!(lengths, vec![40, 40, 20]);
+
+        // A long word after a short one starts on the next line before being split
+        let message = MessageBuilder::default()
+            .text(&format!("hi {}", "b".repeat(50)))
+            .build();
+        assert_eq!(
+            lines(&message),
+            vec!["hi".to_string(), "b".repeat(40), "b".repeat(10)]
+        );
+    }
+
+    #[test]
+    fn size_rescales_line_length() {
+        // Double the size halves the width, so the 10 characters so far count as 5
+        let fits = MessageBuilder::default()
+            .text(&"a".repeat(10))
+            .size(48)
+            .text(&format!(" {}", "b".repeat(14)))
+            .build();
+        assert!(fits.contains("<SZ 48>"));
+        assert_eq!(lines(&fits).len(), 1);
+
+        let wraps = MessageBuilder::default()
+            .text(&"a".repeat(10))
+            .size(48)
+            .text(&format!(" {}", "b".repeat(15)))
+            .build();
+        assert_eq!(lines(&wraps), vec!["a".repeat(10), "b".repeat(15)]);
+    }
+
+    #[test]
+    fn size_ignores_zero_and_unchanged() {
+        let message = MessageBuilder::default().size(0).size(DEFAULT_SIZE).build();
+        assert_eq!(message, format!("{}<NE>\x00", HEADER));
+    }
+
+    #[test]
+    fn escape_replaces_tags_and_non_ascii() {
+        assert_eq!(escape("<BR>"), "[BR]");
+        assert_eq!(escape("Caf\u{e9}\t!"), "Caf??!");
+        assert_eq!(escape("Plain text"), "Plain text");
+    }
+
+    #[test]
+    fn text_is_escaped() {
+        let message = MessageBuilder::default().text("<NE>").build();
+        assert_eq!(message, format!("{}[NE]<NE>\x00", HEADER));
+    }
+
+    #[test]
+    fn truncate_keeps_terminator_and_whole_tags() {
+        let message = MessageBuilder::default().text("one\ntwo").build();
+        assert_eq!(truncate(&message, message.len()), message);
+
+        let cut = truncate(&message, HEADER.len() + "one<B".len() + TERMINATOR.len());
+        assert_eq!(cut, format!("{}one<NE>\x00", HEADER));
+        assert!(cut.len() <= HEADER.len() + "one<B".len() + TERMINATOR.len());
+    }
+}
@@ -0,0 +1,184 @@
+use crate::constants::{GUN_NAMES, MELEE_NAMES, MISSION_ITEM_MAP, VERGIL_MELEE_NAMES};
+use crate::game_manager::{ARCHIPELAGO_DATA, ArchipelagoData, Style, playing_vergil};
+use crate::skill_manager::ID_SKILL_MAP;
+use crate::ui::overlay;
+use crate::ui::overlay::{GREY, MessageSegment, get_default_color};
+use randomizer_utilities::ui::font_handler::{FontColorCB, GREEN, YELLOW};
+use randomizer_utilities::ui::overlay::D3D11State;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the tracker is shown, toggled with F3
+static TRACKER_OPEN: AtomicBool = AtomicBool::new(false);
+/// So a poisoned lock is only reported once rather than every frame
+static READ_ERROR_LOGGED: AtomicBool = AtomicBool::new(false);
+
+pub(crate) fn is_open() -> bool {
+    TRACKER_OPEN.load(Ordering::SeqCst)
+}
+
+pub(crate) fn toggle() {
+    TRACKER_OPEN.fetch_xor(true, Ordering::SeqCst);
+}
+
+fn found_color(found: bool) -> FontColorCB {
+    if found { GREEN } else { GREY }
+}
+
+fn header(text: &str) -> Vec<MessageSegment> {
+    vec![MessageSegment::new(text.to_string(), YELLOW)]
+}
+
+/// A label followed by a list of entries, each colored by whether it's been found
+fn entry_line(label: String, entries: Vec<(String, bool)>) -> Vec<MessageSegment> {
+    let mut line = vec![MessageSegment::new(label, *get_default_color())];
+    for (i, (text, found)) in entries.into_iter().enumerate() {
+        if i > 0 {
+            line.push(MessageSegment::new(", ".to_string(), *get_default_color()));
+        }
+        line.push(MessageSegment::new(text, found_color(found)));
+    }
+    line
+}
+
+fn get_tracker_lines(data: &ArchipelagoData, playing_vergil: bool) -> Vec<Vec<MessageSegment>> {
+    let mut lines = vec![header("Key Items")];
+    let mut missions: Vec<_> = MISSION_ITEM_MAP.iter().collect();
+    missions.sort_by_key(|(mission, _)| **mission);
+    for (mission, items) in missions {
+        lines.push(entry_line(
+            format!("M{}: ", mission),
+            items
+                .iter()
+                .map(|item| (item.to_string(), data.items.contains(*item)))
+                .collect(),
+        ));
+    }
+
+    lines.push(header("Weapons"));
+    let melee = if playing_vergil {
+        &VERGIL_MELEE_NAMES
+    } else {
+        &MELEE_NAMES
+    };
+    lines.push(entry_line(
+        "Melee: ".to_string(),
+        melee
+            .iter()
+            .map(|weapon| (weapon.to_string(), data.items.contains(*weapon)))
+            .collect(),
+    ));
+    if !playing_vergil {
+        lines.push(entry_line(
+            "Guns: ".to_string(),
+            GUN_NAMES
+                .iter()
+                .enumerate()
+                .map(|(i, gun)| {
+                    let found = data.items.contains(*gun);
+                    if found {
+                        (format!("{} Lv{}", gun, data.get_gun_level(i) + 1), found)
+                    } else {
+                        (gun.to_string(), found)
+                    }
+                })
+                .collect(),
+        ));
+        let styles = [
+            Style::Trickster,
+            Style::Swordmaster,
+            Style::Gunslinger,
+            Style::Royalguard,
+            Style::Quicksilver,
+            Style::Doppelganger,
+        ];
+        lines.push(entry_line(
+            "Styles: ".to_string(),
+            styles
+                .iter()
+                .map(|style| {
+                    let level = data.get_style_level(*style);
+                    (format!("{} Lv{}", style, level), level > 0)
+                })
+                .collect(),
+        ));
+    } else {
+        // Dark Slayer sits in Trickster's slot
+        let level = data.get_style_level(Style::Trickster);
+        lines.push(entry_line(
+            "Style: ".to_string(),
+            vec![(format!("Dark Slayer Lv{}", level), level > 0)],
+        ));
+    }
+
+    lines.push(header("Skills"));
+    if playing_vergil {
+        // Vergil's shop isn't randomized yet, so there's nothing to track
+        lines.push(vec![MessageSegment::new(
+            "Bought from the Divinity Statue as usual".to_string(),
+            GREY,
+        )]);
+    } else {
+        for weapon in melee.iter() {
+            let prefix = format!("{} - ", weapon);
+            let mut skills: Vec<(usize, &str)> = ID_SKILL_MAP
+                .iter()
+                .filter_map(|(id, name)| name.strip_prefix(&prefix).map(|skill| (*id, skill)))
+                .collect();
+            if skills.is_empty() {
+                continue;
+            }
+            skills.sort();
+            lines.push(entry_line(
+                format!("{}: ", weapon),
+                skills
+                    .into_iter()
+                    .map(|(id, skill)| (skill.to_string(), data.skills.contains(&id)))
+                    .collect(),
+            ));
+        }
+    }
+
+    lines.push(entry_line(
+        String::new(),
+        vec![
+            (format!("Blue Orbs: {}", data.blue_orbs), true),
+            (format!("Purple Orbs: {}", data.purple_orbs), true),
+        ],
+    ));
+    lines
+}
+
+pub(crate) fn draw_tracker(state: &D3D11State, screen_width: f32, screen_height: f32) {
+    const PADDING: f32 = 12.0;
+    const LINE_HEIGHT: f32 = 24.0;
+    const TOP: f32 = 120.0;
+    if !TRACKER_OPEN.load(Ordering::SeqCst) {
+        return;
+    }
+    let lines = match ARCHIPELAGO_DATA.read() {
+        Ok(data) => get_tracker_lines(&data, playing_vergil()),
+        Err(err) => {
+            if !READ_ERROR_LOGGED.swap(true, Ordering::SeqCst) {
+                log::error!("Failed to read ArchipelagoData: {:?}", err);
+            }
+            return;
+        }
+    };
+    // Lines that don't fit above the bottom of the screen continue in another column
+    let rows = ((screen_height - TOP - PADDING) / LINE_HEIGHT)
+        .floor()
+        .max(1.0) as usize;
+    let mut column_x = PADDING;
+    for column in lines.chunks(rows) {
+        if column_x >= screen_width {
+            break;
+        }
+        let mut column_width: f32 = 0.0;
+        for (i, line) in column.iter().enumerate() {
+            let y = TOP + i as f32 * LINE_HEIGHT;
+            let end = overlay::draw_segments(state, line, column_x, y, screen_width, screen_height);
+            column_width = column_width.max(end - column_x);
+        }
+        column_x += column_width + PADDING * 2.0;
+    }
+}